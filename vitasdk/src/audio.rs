@@ -0,0 +1,38 @@
+#[cfg(feature = "audio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "audio")))]
+pub mod input;
+#[cfg(any(feature = "audio", test))]
+mod level;
+#[cfg(any(feature = "mixer", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mixer")))]
+pub mod mixer;
+#[cfg(feature = "audio")]
+mod port;
+#[cfg(any(all(feature = "audio", feature = "std"), test))]
+mod ring;
+#[cfg(all(feature = "audio", feature = "std"))]
+mod stream;
+
+#[cfg(feature = "audio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "audio")))]
+pub use port::{AudioPort, PortType};
+#[cfg(all(feature = "audio", feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "audio", feature = "std"))))]
+pub use stream::AudioStream;
+
+/// Format of the samples, which are signed 16-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleFormat {
+    MonoS16,
+    /// Samples of left and right channels are interleaved.
+    StereoS16,
+}
+
+impl SampleFormat {
+    pub const fn channels(self) -> usize {
+        match self {
+            SampleFormat::MonoS16 => 1,
+            SampleFormat::StereoS16 => 2,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Microphone input.
+
+use core::{ffi::c_int, mem};
+
+use vitasdk_sys::{
+    sceAudioInGetStatus, sceAudioInInput, sceAudioInOpenPort, sceAudioInReleasePort,
+    SceAudioInPortType, SCE_AUDIO_IN_ERROR_INVALID_SIZE, SCE_AUDIO_IN_GETSTATUS_MUTE,
+    SCE_AUDIO_IN_PARAM_FORMAT_S16_MONO, SCE_AUDIO_IN_PORT_TYPE_RAW, SCE_AUDIO_IN_PORT_TYPE_VOICE,
+};
+
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceError, SceResult};
+
+#[cfg(feature = "std")]
+mod capture;
+
+pub use super::level::{peak_level, rms_level};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use capture::AudioCapture;
+
+/// Returns whether the microphone is muted.
+#[doc(alias = "sceAudioInGetStatus")]
+pub fn is_muted() -> SceResult<bool> {
+    sce_result_usize_from_code(unsafe { sceAudioInGetStatus(SCE_AUDIO_IN_GETSTATUS_MUTE as c_int) })
+        .map(|muted| muted != 0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputPortType {
+    /// Processed for voice chat, only supports 16 kHz.
+    Voice,
+    /// Unprocessed input, supports 16 and 48 kHz.
+    Raw,
+}
+
+impl InputPortType {
+    fn to_raw(self) -> SceAudioInPortType {
+        match self {
+            InputPortType::Voice => SCE_AUDIO_IN_PORT_TYPE_VOICE,
+            InputPortType::Raw => SCE_AUDIO_IN_PORT_TYPE_RAW,
+        }
+    }
+}
+
+/// Audio input port capturing signed 16-bit mono samples.
+#[derive(Debug)]
+pub struct AudioInPort {
+    id: c_int,
+    grain: usize,
+    frequency: u32,
+}
+
+impl AudioInPort {
+    /// Opens a port capturing `grain` samples at a time, which has to be
+    /// 256 for 16 kHz or 512 for 48 kHz.
+    #[doc(alias = "sceAudioInOpenPort")]
+    pub fn open(port_type: InputPortType, grain: usize, frequency: u32) -> SceResult<Self> {
+        let id = sce_result_usize_from_code(unsafe {
+            sceAudioInOpenPort(
+                port_type.to_raw(),
+                grain as c_int,
+                frequency as c_int,
+                SCE_AUDIO_IN_PARAM_FORMAT_S16_MONO,
+            )
+        })?;
+        Ok(AudioInPort {
+            id: id as c_int,
+            grain,
+            frequency,
+        })
+    }
+
+    /// Returns port id to be used with raw `sceAudioIn*` functions.
+    pub fn as_raw_id(&self) -> c_int {
+        self.id
+    }
+
+    /// Number of samples captured at a time.
+    pub fn grain(&self) -> usize {
+        self.grain
+    }
+
+    pub fn frequency(&self) -> u32 {
+        self.frequency
+    }
+
+    /// Waits for the next [`AudioInPort::grain`] samples, which is the
+    /// length `samples` has to be.
+    #[doc(alias = "sceAudioInInput")]
+    pub fn input(&self, samples: &mut [i16]) -> SceResult<()> {
+        if samples.len() != self.grain {
+            return Err(SceError::from_error_code(SCE_AUDIO_IN_ERROR_INVALID_SIZE));
+        }
+        sce_result_usize_from_code(unsafe { sceAudioInInput(self.id, samples.as_mut_ptr().cast()) })
+            .map(drop)
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn release(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).release_()
+    }
+
+    fn release_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceAudioInReleasePort(self.id) })
+    }
+}
+
+impl Drop for AudioInPort {
+    fn drop(&mut self) {
+        let _ = self.release_();
+    }
+}
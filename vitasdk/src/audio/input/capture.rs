@@ -0,0 +1,161 @@
+use alloc::{sync::Arc, vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    io,
+    sync::{Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+use super::AudioInPort;
+use crate::{audio::ring::SampleRing, error::SceResult};
+
+#[derive(Debug)]
+struct State {
+    ring: SampleRing,
+    running: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    available: Condvar,
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Captures samples on a dedicated thread into a ring buffer.
+///
+/// Once the buffer is full the oldest samples are dropped, so a slow reader
+/// only loses the samples it didn't keep up with.
+#[derive(Debug)]
+pub struct AudioCapture {
+    port: Arc<AudioInPort>,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<SceResult<()>>>,
+}
+
+impl AudioCapture {
+    /// Spawns the thread reading from `port`, buffering up to `capacity`
+    /// samples.
+    pub fn start(port: AudioInPort, capacity: usize) -> io::Result<Self> {
+        let capacity = capacity.max(port.grain());
+        let port = Arc::new(port);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                ring: SampleRing::new(capacity),
+                running: true,
+            }),
+            available: Condvar::new(),
+            stop: AtomicBool::new(false),
+        });
+        let thread = thread::Builder::new()
+            .name("SceAudioCapture".into())
+            .spawn({
+                let port = Arc::clone(&port);
+                let shared = Arc::clone(&shared);
+                move || {
+                    let mut buffer = vec![0; port.grain()];
+                    let result = loop {
+                        if shared.stop.load(Ordering::Acquire) {
+                            break Ok(());
+                        }
+                        if let Err(e) = port.input(&mut buffer) {
+                            break Err(e);
+                        }
+                        shared.lock().ring.push(&buffer);
+                        shared.available.notify_all();
+                    };
+                    shared.lock().running = false;
+                    shared.available.notify_all();
+                    result
+                }
+            })?;
+        Ok(AudioCapture {
+            port,
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn port(&self) -> &AudioInPort {
+        &self.port
+    }
+
+    /// Number of buffered samples.
+    pub fn available(&self) -> usize {
+        self.shared.lock().ring.len()
+    }
+
+    /// Number of samples dropped because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.shared.lock().ring.dropped()
+    }
+
+    /// Moves buffered samples into `buf` without waiting, returning how many
+    /// were read.
+    pub fn read(&self, buf: &mut [i16]) -> usize {
+        self.shared.lock().ring.read(buf)
+    }
+
+    /// Waits until `buf` can be filled, up to `timeout` if any.
+    ///
+    /// Returns whether `buf` was filled, which doesn't happen once the
+    /// capture stopped because of an error. Fails right away if `buf` is
+    /// longer than the capacity of the buffer, since it could never be
+    /// filled.
+    pub fn read_exact(&self, buf: &mut [i16], timeout: Option<Duration>) -> bool {
+        let len = buf.len();
+        let state = self.shared.lock();
+        if len > state.ring.capacity() {
+            return false;
+        }
+        let waiting = |state: &mut State| state.running && state.ring.len() < len;
+        let mut state = match timeout {
+            Some(timeout) => {
+                self.shared
+                    .available
+                    .wait_timeout_while(state, timeout, waiting)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            }
+            None => self
+                .shared
+                .available
+                .wait_while(state, waiting)
+                .unwrap_or_else(|e| e.into_inner()),
+        };
+        state.ring.read_exact(buf)
+    }
+
+    /// Stops the thread after the samples being captured, returning the port
+    /// along with the error which stopped the thread if any.
+    pub fn stop(mut self) -> (AudioInPort, SceResult<()>) {
+        self.shared.stop.store(true, Ordering::Release);
+        let result = match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        };
+        let port = Arc::clone(&self.port);
+        drop(self);
+        let port = Arc::into_inner(port).expect("capture thread holds no port reference");
+        (port, result)
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::math::sqrt;
+
+/// Returns the peak level of `samples`, ranging from 0.0 to 1.0.
+pub fn peak_level(samples: &[i16]) -> f32 {
+    let peak = samples
+        .iter()
+        .map(|sample| sample.unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    peak as f32 / 32768.0
+}
+
+/// Returns the root mean square level of `samples`, ranging from 0.0 to 1.0.
+pub fn rms_level(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples
+        .iter()
+        .map(|&sample| {
+            let sample = sample as f32 / 32768.0;
+            sample * sample
+        })
+        .sum();
+    sqrt(sum / samples.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{peak_level, rms_level};
+
+    #[test]
+    fn peak() {
+        assert_eq!(peak_level(&[]), 0.0);
+        assert_eq!(peak_level(&[0, 16384, -8192]), 0.5);
+        assert_eq!(peak_level(&[i16::MIN, 100]), 1.0);
+    }
+
+    #[test]
+    fn rms() {
+        assert_eq!(rms_level(&[]), 0.0);
+        assert_eq!(rms_level(&[0; 16]), 0.0);
+        assert!((rms_level(&[16384, -16384]) - 0.5).abs() < 1e-6);
+        assert!((rms_level(&[8192, 0, -8192, 0]) - 0.176_776_7).abs() < 1e-6);
+    }
+}
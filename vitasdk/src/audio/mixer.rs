@@ -0,0 +1,329 @@
+//! Software mixer playing several sounds at once.
+//!
+//! [`Mixer::render`] doesn't call any system function, so it can render into
+//! any buffer, while `stream` plays it on an `AudioPort` with the `audio` and
+//! `std` features.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use super::SampleFormat;
+#[cfg(all(feature = "audio", feature = "std"))]
+use super::{AudioPort, AudioStream};
+
+/// Fractional bits of the playback position.
+const FRAC_BITS: u32 = 32;
+
+/// Plays `mixer` on `port` with an [`AudioStream`], the mixer being locked
+/// while each buffer is rendered.
+#[cfg(all(feature = "audio", feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "audio", feature = "std"))))]
+pub fn stream(
+    port: AudioPort,
+    mixer: Arc<std::sync::Mutex<Mixer>>,
+) -> std::io::Result<AudioStream> {
+    AudioStream::start(port, move |buffer| match mixer.lock() {
+        Ok(mut mixer) => mixer.render(buffer),
+        Err(_) => buffer.fill(0),
+    })
+}
+
+/// Sound data shared by the voices playing it.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    samples: Arc<[i16]>,
+    format: SampleFormat,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Stereo samples are interleaved, any sample rate is converted to the
+    /// one of the mixer.
+    pub fn new(samples: impl Into<Arc<[i16]>>, format: SampleFormat, sample_rate: u32) -> Self {
+        Sound {
+            samples: samples.into(),
+            format,
+            sample_rate: sample_rate.max(1),
+        }
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of samples per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.format.channels()
+    }
+
+    fn frame(&self, index: usize) -> (i32, i32) {
+        match self.format {
+            SampleFormat::MonoS16 => {
+                let sample = self.samples[index] as i32;
+                (sample, sample)
+            }
+            SampleFormat::StereoS16 => (
+                self.samples[2 * index] as i32,
+                self.samples[2 * index + 1] as i32,
+            ),
+        }
+    }
+}
+
+/// Handle of a sound played by a [`Mixer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoiceId(u32);
+
+#[derive(Debug)]
+struct Voice {
+    id: VoiceId,
+    sound: Sound,
+    position: u64,
+    step: u64,
+    volume: f32,
+    pan: f32,
+    looping: bool,
+}
+
+impl Voice {
+    /// Returns the interpolated frame at the current position, or `None`
+    /// once the sound is over.
+    fn next_frame(&mut self) -> Option<(i32, i32)> {
+        let frames = self.sound.frames() as u64;
+        if frames == 0 {
+            return None;
+        }
+        if self.position >> FRAC_BITS >= frames {
+            match self.looping {
+                true => self.position %= frames << FRAC_BITS,
+                false => return None,
+            }
+        }
+        let index = (self.position >> FRAC_BITS) as usize;
+        let next = match index + 1 < frames as usize {
+            true => index + 1,
+            false if self.looping => 0,
+            false => index,
+        };
+        let frac = (self.position & ((1 << FRAC_BITS) - 1)) as i64;
+        let (l0, r0) = self.sound.frame(index);
+        let (l1, r1) = self.sound.frame(next);
+        let lerp = |a: i32, b: i32| (a as i64 + (((b - a) as i64 * frac) >> FRAC_BITS)) as i32;
+        self.position += self.step;
+        Some((lerp(l0, l1), lerp(r0, r1)))
+    }
+
+    fn gains(&self) -> (f32, f32) {
+        (
+            self.volume * (1.0 - self.pan).min(1.0),
+            self.volume * (1.0 + self.pan).min(1.0),
+        )
+    }
+}
+
+/// Mixes voices into buffers of interleaved samples.
+#[derive(Debug)]
+pub struct Mixer {
+    sample_rate: u32,
+    format: SampleFormat,
+    volume: f32,
+    voices: Vec<Voice>,
+    next_id: u32,
+    scratch: Vec<i32>,
+}
+
+impl Mixer {
+    /// Creates a mixer rendering buffers with `format` samples at
+    /// `sample_rate`, which should match the output port.
+    pub fn new(sample_rate: u32, format: SampleFormat) -> Self {
+        Mixer {
+            sample_rate: sample_rate.max(1),
+            format,
+            volume: 1.0,
+            voices: Vec::new(),
+            next_id: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Sets volume applied to all of the voices, 1.0 by default.
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.volume = volume.max(0.0);
+    }
+
+    /// Starts playing `sound` at full volume and centered.
+    pub fn play(&mut self, sound: &Sound) -> VoiceId {
+        let id = VoiceId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.voices.push(Voice {
+            id,
+            sound: sound.clone(),
+            position: 0,
+            step: ((sound.sample_rate as u64) << FRAC_BITS) / self.sample_rate as u64,
+            volume: 1.0,
+            pan: 0.0,
+            looping: false,
+        });
+        id
+    }
+
+    /// Starts playing `sound` over and over until stopped.
+    pub fn play_looping(&mut self, sound: &Sound) -> VoiceId {
+        let id = self.play(sound);
+        self.set_looping(id, true);
+        id
+    }
+
+    pub fn stop(&mut self, voice: VoiceId) {
+        self.voices.retain(|v| v.id != voice);
+    }
+
+    pub fn stop_all(&mut self) {
+        self.voices.clear();
+    }
+
+    /// Returns `false` once the voice was stopped or reached the end of its
+    /// sound.
+    pub fn is_playing(&self, voice: VoiceId) -> bool {
+        self.voices.iter().any(|v| v.id == voice)
+    }
+
+    /// Number of voices playing.
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Sets volume of the voice, 1.0 being the volume of the sound.
+    pub fn set_volume(&mut self, voice: VoiceId, volume: f32) {
+        if let Some(v) = self.voice_mut(voice) {
+            v.volume = volume.max(0.0);
+        }
+    }
+
+    /// Sets panning of the voice from -1.0 for left to 1.0 for right.
+    pub fn set_pan(&mut self, voice: VoiceId, pan: f32) {
+        if let Some(v) = self.voice_mut(voice) {
+            v.pan = pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    pub fn set_looping(&mut self, voice: VoiceId, looping: bool) {
+        if let Some(v) = self.voice_mut(voice) {
+            v.looping = looping;
+        }
+    }
+
+    /// Fills `buffer` with the next samples of all of the voices, removing
+    /// those which ended.
+    pub fn render(&mut self, buffer: &mut [i16]) {
+        let channels = self.format.channels();
+        self.scratch.clear();
+        self.scratch.resize(buffer.len(), 0);
+
+        let volume = self.volume;
+        self.voices.retain_mut(|voice| {
+            let (left_gain, right_gain) = voice.gains();
+            let (left_gain, right_gain) = (left_gain * volume, right_gain * volume);
+            for frame in self.scratch.chunks_exact_mut(channels) {
+                let Some((left, right)) = voice.next_frame() else {
+                    return false;
+                };
+                let (left, right) = (left as f32 * left_gain, right as f32 * right_gain);
+                match frame {
+                    [mono] => *mono += ((left + right) / 2.0) as i32,
+                    [l, r] => {
+                        *l += left as i32;
+                        *r += right as i32;
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            true
+        });
+
+        for (out, mixed) in buffer.iter_mut().zip(&self.scratch) {
+            *out = (*mixed).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        }
+    }
+
+    fn voice_mut(&mut self, voice: VoiceId) -> Option<&mut Voice> {
+        self.voices.iter_mut().find(|v| v.id == voice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mixer, SampleFormat, Sound};
+
+    fn render(mixer: &mut Mixer, len: usize) -> alloc::vec::Vec<i16> {
+        let mut buffer = alloc::vec![0x55; len];
+        mixer.render(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn gain() {
+        let sound = Sound::new([1000; 4], SampleFormat::MonoS16, 48000);
+        let mut mixer = Mixer::new(48000, SampleFormat::StereoS16);
+        let voice = mixer.play(&sound);
+        mixer.set_volume(voice, 0.5);
+        assert_eq!(render(&mut mixer, 4), [500; 4]);
+        mixer.set_master_volume(0.5);
+        assert_eq!(render(&mut mixer, 4), [250; 4]);
+    }
+
+    #[test]
+    fn panning() {
+        let sound = Sound::new([1000; 4], SampleFormat::MonoS16, 48000);
+        let mut mixer = Mixer::new(48000, SampleFormat::StereoS16);
+        let voice = mixer.play(&sound);
+        mixer.set_pan(voice, -1.0);
+        assert_eq!(render(&mut mixer, 2), [1000, 0]);
+        mixer.set_pan(voice, 1.0);
+        assert_eq!(render(&mut mixer, 2), [0, 1000]);
+        mixer.set_pan(voice, 0.5);
+        assert_eq!(render(&mut mixer, 2), [500, 1000]);
+    }
+
+    #[test]
+    fn clipping() {
+        let loud = Sound::new([30000, -30000], SampleFormat::StereoS16, 48000);
+        let mut mixer = Mixer::new(48000, SampleFormat::StereoS16);
+        mixer.play(&loud);
+        mixer.play(&loud);
+        assert_eq!(render(&mut mixer, 2), [i16::MAX, i16::MIN]);
+    }
+
+    #[test]
+    fn voice_end() {
+        let sound = Sound::new([100, 200], SampleFormat::MonoS16, 48000);
+        let mut mixer = Mixer::new(48000, SampleFormat::MonoS16);
+        let voice = mixer.play(&sound);
+        let looping = mixer.play_looping(&sound);
+        mixer.set_volume(looping, 0.0);
+        assert_eq!(render(&mut mixer, 4), [100, 200, 0, 0]);
+        assert!(!mixer.is_playing(voice));
+        assert!(mixer.is_playing(looping));
+        assert_eq!(mixer.voice_count(), 1);
+    }
+
+    #[test]
+    fn resampling() {
+        let sound = Sound::new([100, 200], SampleFormat::MonoS16, 24000);
+        let mut mixer = Mixer::new(48000, SampleFormat::MonoS16);
+        let voice = mixer.play(&sound);
+        mixer.set_looping(voice, true);
+        assert_eq!(render(&mut mixer, 6), [100, 150, 200, 150, 100, 150]);
+    }
+}
@@ -0,0 +1,181 @@
+use core::{ffi::c_int, mem};
+
+use vitasdk_sys::{
+    sceAudioOutGetRestSample, sceAudioOutOpenPort, sceAudioOutOutput, sceAudioOutReleasePort,
+    sceAudioOutSetConfig, sceAudioOutSetVolume, SceAudioOutMode, SceAudioOutPortType,
+    SCE_AUDIO_OUT_ERROR_INVALID_SIZE, SCE_AUDIO_OUT_MAX_VOL, SCE_AUDIO_OUT_MODE_MONO,
+    SCE_AUDIO_OUT_MODE_STEREO, SCE_AUDIO_OUT_PORT_TYPE_BGM, SCE_AUDIO_OUT_PORT_TYPE_MAIN,
+    SCE_AUDIO_OUT_PORT_TYPE_VOICE, SCE_AUDIO_VOLUME_FLAG_L_CH, SCE_AUDIO_VOLUME_FLAG_R_CH,
+};
+
+use super::SampleFormat;
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceError, SceResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortType {
+    /// Sound effects, only supports 48 kHz.
+    Main,
+    /// Background music, supports frequencies from 8 to 48 kHz.
+    Bgm,
+    /// Voice chat, only supports 48 kHz.
+    Voice,
+}
+
+impl PortType {
+    fn to_raw(self) -> SceAudioOutPortType {
+        match self {
+            PortType::Main => SCE_AUDIO_OUT_PORT_TYPE_MAIN,
+            PortType::Bgm => SCE_AUDIO_OUT_PORT_TYPE_BGM,
+            PortType::Voice => SCE_AUDIO_OUT_PORT_TYPE_VOICE,
+        }
+    }
+}
+
+impl SampleFormat {
+    fn to_raw(self) -> SceAudioOutMode {
+        match self {
+            SampleFormat::MonoS16 => SCE_AUDIO_OUT_MODE_MONO,
+            SampleFormat::StereoS16 => SCE_AUDIO_OUT_MODE_STEREO,
+        }
+    }
+}
+
+/// Audio output port.
+#[derive(Debug)]
+pub struct AudioPort {
+    id: c_int,
+    grain: usize,
+    frequency: u32,
+    format: SampleFormat,
+}
+
+impl AudioPort {
+    pub const DEFAULT_FREQUENCY: u32 = 48000;
+
+    /// Opens a port outputting `grain` samples per channel at a time, which
+    /// has to be a multiple of 64 up to 65472.
+    #[doc(alias = "sceAudioOutOpenPort")]
+    pub fn open(
+        port_type: PortType,
+        grain: usize,
+        frequency: u32,
+        format: SampleFormat,
+    ) -> SceResult<Self> {
+        let id = sce_result_usize_from_code(unsafe {
+            sceAudioOutOpenPort(
+                port_type.to_raw(),
+                grain as c_int,
+                frequency as c_int,
+                format.to_raw(),
+            )
+        })?;
+        Ok(AudioPort {
+            id: id as c_int,
+            grain,
+            frequency,
+            format,
+        })
+    }
+
+    /// Returns port id to be used with raw `sceAudioOut*` functions.
+    pub fn as_raw_id(&self) -> c_int {
+        self.id
+    }
+
+    /// Number of samples per channel output at a time.
+    pub fn grain(&self) -> usize {
+        self.grain
+    }
+
+    pub fn frequency(&self) -> u32 {
+        self.frequency
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Number of samples in the buffers passed to [`AudioPort::output`].
+    pub fn buffer_len(&self) -> usize {
+        self.grain * self.format.channels()
+    }
+
+    /// Queues `samples` for playback, blocking until the previously queued
+    /// buffer has been played.
+    ///
+    /// Length of `samples` has to be [`AudioPort::buffer_len`], buffers are
+    /// usually alternated, which `AudioStream` does safely.
+    ///
+    /// # Safety
+    ///
+    /// The hardware keeps reading from `samples` after this returns, so they
+    /// must neither be modified nor freed until the next call to
+    /// [`AudioPort::output`] or [`AudioPort::drain`] returns.
+    #[doc(alias = "sceAudioOutOutput")]
+    pub unsafe fn output(&self, samples: &[i16]) -> SceResult<()> {
+        if samples.len() != self.buffer_len() {
+            return Err(SceError::from_error_code(SCE_AUDIO_OUT_ERROR_INVALID_SIZE));
+        }
+        sce_result_usize_from_code(unsafe { sceAudioOutOutput(self.id, samples.as_ptr().cast()) })
+            .map(drop)
+    }
+
+    /// Waits until the last queued buffer has been played.
+    #[doc(alias = "sceAudioOutOutput")]
+    pub fn drain(&self) -> SceResult<()> {
+        sce_result_usize_from_code(unsafe { sceAudioOutOutput(self.id, core::ptr::null()) })
+            .map(drop)
+    }
+
+    /// Number of samples per channel not played yet.
+    #[doc(alias = "sceAudioOutGetRestSample")]
+    pub fn rest_samples(&self) -> SceResult<usize> {
+        sce_result_usize_from_code(unsafe { sceAudioOutGetRestSample(self.id) })
+    }
+
+    /// Sets volume of the channels, ranging from 0.0 to 1.0.
+    #[doc(alias = "sceAudioOutSetVolume")]
+    pub fn set_volume(&self, left: f32, right: f32) -> SceResult<()> {
+        let to_raw = |volume: f32| (volume.clamp(0.0, 1.0) * SCE_AUDIO_OUT_MAX_VOL as f32) as c_int;
+        let mut volume = [to_raw(left), to_raw(right)];
+        sce_result_unit_from_code(unsafe {
+            sceAudioOutSetVolume(
+                self.id,
+                SCE_AUDIO_VOLUME_FLAG_L_CH | SCE_AUDIO_VOLUME_FLAG_R_CH,
+                volume.as_mut_ptr(),
+            )
+        })
+    }
+
+    /// Changes grain, frequency and format of the port.
+    #[doc(alias = "sceAudioOutSetConfig")]
+    pub fn set_config(
+        &mut self,
+        grain: usize,
+        frequency: u32,
+        format: SampleFormat,
+    ) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceAudioOutSetConfig(self.id, grain as u32, frequency as c_int, format.to_raw())
+        })?;
+        self.grain = grain;
+        self.frequency = frequency;
+        self.format = format;
+        Ok(())
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn release(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).release_()
+    }
+
+    fn release_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceAudioOutReleasePort(self.id) })
+    }
+}
+
+impl Drop for AudioPort {
+    fn drop(&mut self) {
+        let _ = self.release_();
+    }
+}
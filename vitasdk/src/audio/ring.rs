@@ -0,0 +1,106 @@
+use alloc::collections::VecDeque;
+
+/// Bounded queue of samples dropping the oldest ones once full.
+#[derive(Debug)]
+pub(crate) struct SampleRing {
+    samples: VecDeque<i16>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl SampleRing {
+    pub(crate) fn new(capacity: usize) -> Self {
+        SampleRing {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of samples dropped because the ring was full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Appends `data`, dropping as many of the oldest samples as needed.
+    pub(crate) fn push(&mut self, data: &[i16]) {
+        let kept = &data[data.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + kept.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.dropped += (overflow + data.len() - kept.len()) as u64;
+        self.samples.extend(kept);
+    }
+
+    /// Moves the oldest samples into `buf`, returning how many were moved.
+    pub(crate) fn read(&mut self, buf: &mut [i16]) -> usize {
+        let n = buf.len().min(self.samples.len());
+        for (out, sample) in buf.iter_mut().zip(self.samples.drain(..n)) {
+            *out = sample;
+        }
+        n
+    }
+
+    /// Fills `buf` with the oldest samples, returning `false` without
+    /// reading anything if there aren't enough of them.
+    pub(crate) fn read_exact(&mut self, buf: &mut [i16]) -> bool {
+        if self.samples.len() < buf.len() {
+            return false;
+        }
+        self.read(buf);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SampleRing;
+
+    #[test]
+    fn read_in_order() {
+        let mut ring = SampleRing::new(8);
+        ring.push(&[1, 2, 3]);
+        ring.push(&[4, 5]);
+        let mut buf = [0; 4];
+        assert_eq!(ring.read(&mut buf), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(ring.read(&mut buf), 1);
+        assert_eq!(buf[0], 5);
+        assert_eq!(ring.read(&mut buf), 0);
+        assert_eq!(ring.dropped(), 0);
+    }
+
+    #[test]
+    fn drops_oldest_once_full() {
+        let mut ring = SampleRing::new(4);
+        ring.push(&[1, 2, 3]);
+        ring.push(&[4, 5, 6]);
+        assert_eq!((ring.len(), ring.capacity()), (4, 4));
+        assert_eq!(ring.dropped(), 2);
+        ring.push(&[7, 8, 9, 10, 11, 12]);
+        assert_eq!(ring.dropped(), 8);
+        let mut buf = [0; 4];
+        assert!(ring.read_exact(&mut buf));
+        assert_eq!(buf, [9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn read_exact_needs_enough_samples() {
+        let mut ring = SampleRing::new(4);
+        ring.push(&[1, 2, 3]);
+        let mut buf = [0; 4];
+        assert!(!ring.read_exact(&mut buf));
+        assert_eq!(ring.len(), 3);
+        ring.push(&[4]);
+        assert!(ring.read_exact(&mut buf));
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(ring.len(), 0);
+    }
+}
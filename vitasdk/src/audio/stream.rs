@@ -0,0 +1,97 @@
+use alloc::{sync::Arc, vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    io,
+    thread::{self, JoinHandle},
+};
+
+use super::AudioPort;
+use crate::error::SceResult;
+
+/// Plays samples pulled from a callback on a dedicated thread.
+///
+/// The thread alternates between two buffers of [`AudioPort::buffer_len`]
+/// samples, so the callback fills one while the other is being played.
+#[derive(Debug)]
+pub struct AudioStream {
+    port: Arc<AudioPort>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<SceResult<()>>>,
+}
+
+impl AudioStream {
+    /// Spawns the thread feeding `port`, `callback` is called with the
+    /// buffer to fill with interleaved samples.
+    pub fn start<F>(port: AudioPort, mut callback: F) -> io::Result<Self>
+    where
+        F: FnMut(&mut [i16]) + Send + 'static,
+    {
+        let port = Arc::new(port);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = thread::Builder::new()
+            .name("SceAudioStream".into())
+            .spawn({
+                let port = Arc::clone(&port);
+                let stop = Arc::clone(&stop);
+                move || {
+                    let mut buffers = [vec![0; port.buffer_len()], vec![0; port.buffer_len()]];
+                    let mut current = 0;
+                    while !stop.load(Ordering::Acquire) {
+                        let buffer = &mut buffers[current];
+                        callback(buffer);
+                        // The buffer isn't touched again until the next
+                        // output returns, nor freed before draining.
+                        if let Err(e) = unsafe { port.output(buffer) } {
+                            let _ = port.drain();
+                            return Err(e);
+                        }
+                        current ^= 1;
+                    }
+                    port.drain()
+                }
+            })?;
+        Ok(AudioStream {
+            port,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn port(&self) -> &AudioPort {
+        &self.port
+    }
+
+    /// Sets volume of the channels, ranging from 0.0 to 1.0.
+    pub fn set_volume(&self, left: f32, right: f32) -> SceResult<()> {
+        self.port.set_volume(left, right)
+    }
+
+    /// Returns whether the thread stopped because of an error.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Stops the thread after the buffer being played, returning the port
+    /// along with the error which stopped the thread if any.
+    pub fn stop(mut self) -> (AudioPort, SceResult<()>) {
+        self.stop.store(true, Ordering::Release);
+        let result = match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        };
+        let port = Arc::clone(&self.port);
+        drop(self);
+        let port = Arc::into_inner(port).expect("audio thread holds no port reference");
+        (port, result)
+    }
+}
+
+impl Drop for AudioStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
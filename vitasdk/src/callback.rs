@@ -0,0 +1,198 @@
+//! Kernel callbacks.
+//!
+//! A callback belongs to the thread which created it, and only runs on that
+//! thread while it is in a callback-aware wait: [`check`], [`sleep`] or one
+//! of the `*_cb` variants of the waits of `thread` and `sync` objects.
+//! Plain waits leave notifications pending, so a thread should either poll
+//! with [`check`] from its loop, or be dedicated to sleeping in [`sleep`]
+//! for the callbacks to run promptly.
+
+use alloc::boxed::Box;
+use core::{
+    cell::{Cell, UnsafeCell},
+    ffi::{c_int, c_void, CStr},
+    fmt,
+    marker::PhantomData,
+    mem,
+    ptr::NonNull,
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceKernelCheckCallback, sceKernelCreateCallback, sceKernelDelayThreadCB,
+    sceKernelDeleteCallback, sceKernelGetCallbackCount, sceKernelNotifyCallback,
+};
+
+use crate::{
+    error::{
+        sce_result_uid_from_code, sce_result_unit_from_code, sce_result_usize_from_code, SceResult,
+    },
+    types::Uid,
+};
+
+/// Runs the pending callbacks of the calling thread, returning whether any
+/// of them ran.
+#[doc(alias = "sceKernelCheckCallback")]
+pub fn check() -> SceResult<bool> {
+    sce_result_usize_from_code(unsafe { sceKernelCheckCallback() }).map(|ran| ran != 0)
+}
+
+/// Suspends the calling thread with microsecond precision, running its
+/// callbacks as they are notified.
+#[doc(alias = "sceKernelDelayThreadCB")]
+pub fn sleep(duration: Duration) -> SceResult<()> {
+    let mut usec = duration.as_micros();
+    while usec > 0 {
+        let delay = usec.min(u32::MAX as u128) as u32;
+        sce_result_unit_from_code(unsafe { sceKernelDelayThreadCB(delay) })?;
+        usec -= delay as u128;
+    }
+    Ok(())
+}
+
+/// Notifications received since the callback last ran.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Notification {
+    /// Number of notifications, which are merged until the callback runs.
+    pub count: u32,
+    /// Argument of the latest notification.
+    pub arg: i32,
+}
+
+/// Kernel callback running a closure on the thread which created it.
+///
+/// The callback is deleted on drop, and it can't be sent to another thread
+/// as it would keep running on the creating one.
+///
+/// The closure isn't run again while it is running, so notifications handled
+/// by a callback-aware wait inside of it are dropped. It must not delete its
+/// own callback either, which leaks the closure instead of freeing it under
+/// its feet.
+pub struct Callback<F> {
+    uid: Uid,
+    callback: NonNull<Closure<F>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<F> Callback<F>
+where
+    F: FnMut(Notification) + 'static,
+{
+    #[doc(alias = "sceKernelCreateCallback")]
+    pub fn new(name: &CStr, callback: F) -> SceResult<Self> {
+        let callback = NonNull::from(Box::leak(Box::new(Closure {
+            running: Cell::new(false),
+            f: UnsafeCell::new(callback),
+        })));
+        let res = sce_result_uid_from_code(unsafe {
+            sceKernelCreateCallback(
+                name.as_ptr(),
+                0,
+                Some(trampoline::<F>),
+                callback.as_ptr().cast(),
+            )
+        });
+        match res {
+            Ok(uid) => Ok(Callback {
+                uid,
+                callback,
+                _not_send: PhantomData,
+            }),
+            Err(e) => {
+                drop(unsafe { Box::from_raw(callback.as_ptr()) });
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<F> Callback<F> {
+    /// Returns uid to register the callback with system events.
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Returns a handle notifying the callback from any thread.
+    pub fn notifier(&self) -> Notifier {
+        Notifier { uid: self.uid }
+    }
+
+    /// Number of notifications waiting for the callback to run.
+    #[doc(alias = "sceKernelGetCallbackCount")]
+    pub fn pending(&self) -> SceResult<u32> {
+        sce_result_usize_from_code(unsafe { sceKernelGetCallbackCount(self.uid.get()) })
+            .map(|count| count as u32)
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteCallback(self.uid.get()) })?;
+        // Leaked on error since the callback could still be invoked, and when
+        // deleted by its own closure which is still running
+        if !unsafe { self.callback.as_ref() }.running.get() {
+            drop(unsafe { Box::from_raw(self.callback.as_ptr()) });
+        }
+        Ok(())
+    }
+}
+
+impl<F> Drop for Callback<F> {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
+
+impl<F> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Callback")
+            .field("uid", &self.uid)
+            .finish_non_exhaustive()
+    }
+}
+
+struct Closure<F> {
+    running: Cell<bool>,
+    f: UnsafeCell<F>,
+}
+
+unsafe extern "C" fn trampoline<F>(
+    _notify_id: c_int,
+    count: c_int,
+    arg: c_int,
+    user_data: *mut c_void,
+) -> c_int
+where
+    F: FnMut(Notification),
+{
+    let closure = unsafe { &*user_data.cast::<Closure<F>>() };
+    // Nested runs from waits of the closure would alias it
+    if !closure.running.replace(true) {
+        let f = unsafe { &mut *closure.f.get() };
+        f(Notification {
+            count: count as u32,
+            arg,
+        });
+        closure.running.set(false);
+    }
+    // Keeps the callback registered
+    0
+}
+
+/// Notifies a [`Callback`], which runs on its own thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Notifier {
+    uid: Uid,
+}
+
+impl Notifier {
+    /// Fails once the callback was deleted.
+    #[doc(alias = "sceKernelNotifyCallback")]
+    pub fn notify(&self, arg: i32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelNotifyCallback(self.uid.get(), arg) })
+    }
+}
@@ -0,0 +1,158 @@
+use core::ops;
+
+use vitasdk_sys::{
+    SceCtrlButtons, SCE_CTRL_CIRCLE, SCE_CTRL_CROSS, SCE_CTRL_DOWN, SCE_CTRL_HEADPHONE,
+    SCE_CTRL_INTERCEPTED, SCE_CTRL_L1, SCE_CTRL_L2, SCE_CTRL_L3, SCE_CTRL_LEFT, SCE_CTRL_POWER,
+    SCE_CTRL_R1, SCE_CTRL_R2, SCE_CTRL_R3, SCE_CTRL_RIGHT, SCE_CTRL_SELECT, SCE_CTRL_SQUARE,
+    SCE_CTRL_START, SCE_CTRL_TRIANGLE, SCE_CTRL_TYPE_DS3, SCE_CTRL_TYPE_DS4, SCE_CTRL_TYPE_PHY,
+    SCE_CTRL_TYPE_UNPAIRED, SCE_CTRL_TYPE_VIRT, SCE_CTRL_UP, SCE_CTRL_VOLDOWN, SCE_CTRL_VOLUP,
+};
+
+mod input;
+#[cfg(feature = "ctrl")]
+mod pad;
+
+pub use input::{ControllerState, Stick};
+#[cfg(feature = "ctrl")]
+pub use pad::{
+    is_multi_controller_supported, port_info, sampling_mode, set_sampling_mode,
+    set_sampling_mode_ext, Controller, SamplingMode,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerType {
+    Unpaired,
+    /// Built-in controller.
+    Physical,
+    /// Virtual controller of PS TV.
+    Virtual,
+    Ds3,
+    Ds4,
+    Other(u8),
+}
+
+impl ControllerType {
+    pub fn from_raw(raw: u8) -> Self {
+        match raw as u32 {
+            SCE_CTRL_TYPE_UNPAIRED => ControllerType::Unpaired,
+            SCE_CTRL_TYPE_PHY => ControllerType::Physical,
+            SCE_CTRL_TYPE_VIRT => ControllerType::Virtual,
+            SCE_CTRL_TYPE_DS3 => ControllerType::Ds3,
+            SCE_CTRL_TYPE_DS4 => ControllerType::Ds4,
+            _ => ControllerType::Other(raw),
+        }
+    }
+}
+
+/// Controller port.
+///
+/// Port 0 is the built-in controller, while ports 1 to 4 are the external
+/// controllers of PS TV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Port(u8);
+
+impl Port {
+    pub const COUNT: usize = 5;
+    pub const BUILT_IN: Self = Port(0);
+
+    /// Returns `None` for ports above 4.
+    pub const fn new(index: u8) -> Option<Self> {
+        match index as usize {
+            index if index < Self::COUNT => Some(Port(index as u8)),
+            _ => None,
+        }
+    }
+
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// Set of controller buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Buttons(SceCtrlButtons);
+
+impl Buttons {
+    pub const SELECT: Self = Buttons(SCE_CTRL_SELECT);
+    /// Only reported by external controllers.
+    pub const L3: Self = Buttons(SCE_CTRL_L3);
+    /// Only reported by external controllers.
+    pub const R3: Self = Buttons(SCE_CTRL_R3);
+    pub const START: Self = Buttons(SCE_CTRL_START);
+    pub const UP: Self = Buttons(SCE_CTRL_UP);
+    pub const RIGHT: Self = Buttons(SCE_CTRL_RIGHT);
+    pub const DOWN: Self = Buttons(SCE_CTRL_DOWN);
+    pub const LEFT: Self = Buttons(SCE_CTRL_LEFT);
+    /// Left trigger of the built-in controller, or L2 of external ones.
+    pub const L2: Self = Buttons(SCE_CTRL_L2);
+    /// Right trigger of the built-in controller, or R2 of external ones.
+    pub const R2: Self = Buttons(SCE_CTRL_R2);
+    /// Only reported by external controllers.
+    pub const L1: Self = Buttons(SCE_CTRL_L1);
+    /// Only reported by external controllers.
+    pub const R1: Self = Buttons(SCE_CTRL_R1);
+    pub const TRIANGLE: Self = Buttons(SCE_CTRL_TRIANGLE);
+    pub const CIRCLE: Self = Buttons(SCE_CTRL_CIRCLE);
+    pub const CROSS: Self = Buttons(SCE_CTRL_CROSS);
+    pub const SQUARE: Self = Buttons(SCE_CTRL_SQUARE);
+    pub const INTERCEPTED: Self = Buttons(SCE_CTRL_INTERCEPTED);
+    pub const HEADPHONE: Self = Buttons(SCE_CTRL_HEADPHONE);
+    pub const VOLUP: Self = Buttons(SCE_CTRL_VOLUP);
+    pub const VOLDOWN: Self = Buttons(SCE_CTRL_VOLDOWN);
+    pub const POWER: Self = Buttons(SCE_CTRL_POWER);
+
+    pub const fn empty() -> Self {
+        Buttons(0)
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Buttons(bits)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl ops::BitOr for Buttons {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Buttons(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for Buttons {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl ops::BitAnd for Buttons {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Buttons(self.0 & rhs.0)
+    }
+}
+
+impl ops::Sub for Buttons {
+    type Output = Self;
+
+    /// Buttons of `self` which aren't in `rhs`.
+    fn sub(self, rhs: Self) -> Self {
+        Buttons(self.0 & !rhs.0)
+    }
+}
@@ -0,0 +1,142 @@
+use super::Buttons;
+
+/// Analog stick position, each axis ranging from -1.0 to 1.0 with positive
+/// values pointing right and down.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Stick {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Stick {
+    /// Normalizes raw axis values, ignoring offsets below `deadzone` on each
+    /// axis and rescaling the remaining range.
+    pub fn from_raw(x: u8, y: u8, deadzone: f32) -> Self {
+        Stick {
+            x: normalize_axis(x, deadzone),
+            y: normalize_axis(y, deadzone),
+        }
+    }
+}
+
+fn normalize_axis(raw: u8, deadzone: f32) -> f32 {
+    let value = ((raw as f32 - 127.5) / 127.5).clamp(-1.0, 1.0);
+    let magnitude = match value < 0.0 {
+        true => -value,
+        false => value,
+    };
+    if magnitude <= deadzone {
+        return 0.0;
+    }
+    let scaled = (magnitude - deadzone) / (1.0 - deadzone);
+    match value < 0.0 {
+        true => -scaled,
+        false => scaled,
+    }
+}
+
+/// Controller sample along with the button changes since the previous one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ControllerState {
+    /// Sampling time in microseconds.
+    pub timestamp: u64,
+    /// Buttons held down.
+    pub buttons: Buttons,
+    /// Buttons pressed since the previous sample.
+    pub pressed: Buttons,
+    /// Buttons released since the previous sample.
+    pub released: Buttons,
+    pub left_stick: Stick,
+    pub right_stick: Stick,
+}
+
+impl ControllerState {
+    /// Creates state of a sample, detecting button changes since the sample
+    /// in which `previous` buttons were held down.
+    pub(super) fn new(
+        timestamp: u64,
+        buttons: Buttons,
+        previous: Buttons,
+        left_stick: Stick,
+        right_stick: Stick,
+    ) -> Self {
+        ControllerState {
+            timestamp,
+            buttons,
+            pressed: buttons - previous,
+            released: previous - buttons,
+            left_stick,
+            right_stick,
+        }
+    }
+
+    pub fn is_down(&self, buttons: Buttons) -> bool {
+        self.buttons.contains(buttons)
+    }
+
+    pub fn is_pressed(&self, buttons: Buttons) -> bool {
+        self.pressed.intersects(buttons)
+    }
+
+    pub fn is_released(&self, buttons: Buttons) -> bool {
+        self.released.intersects(buttons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(buttons: Buttons, previous: Buttons) -> ControllerState {
+        ControllerState::new(0, buttons, previous, Stick::default(), Stick::default())
+    }
+
+    #[test]
+    fn axis_range() {
+        assert_eq!(normalize_axis(0, 0.0), -1.0);
+        assert_eq!(normalize_axis(255, 0.0), 1.0);
+        assert!(normalize_axis(128, 0.0).abs() < 0.01);
+        assert!(normalize_axis(64, 0.0) < 0.0);
+        assert!(normalize_axis(192, 0.0) > 0.0);
+    }
+
+    #[test]
+    fn axis_deadzone() {
+        // Center of the stick is 127.5, so both 127 and 128 are within any deadzone
+        assert_eq!(normalize_axis(127, 0.1), 0.0);
+        assert_eq!(normalize_axis(128, 0.1), 0.0);
+        assert_eq!(normalize_axis(140, 0.1), 0.0);
+        assert_eq!(normalize_axis(115, 0.1), 0.0);
+        // Remaining range is rescaled to still reach the ends
+        assert_eq!(normalize_axis(0, 0.5), -1.0);
+        assert_eq!(normalize_axis(255, 0.5), 1.0);
+        let value = normalize_axis(223, 0.5);
+        assert!((value - 0.5).abs() < 0.01, "{value}");
+    }
+
+    #[test]
+    fn stick_axes() {
+        let stick = Stick::from_raw(0, 255, 0.1);
+        assert_eq!(stick, Stick { x: -1.0, y: 1.0 });
+    }
+
+    #[test]
+    fn button_edges() {
+        let held = state(Buttons::CROSS, Buttons::empty());
+        assert_eq!(held.pressed, Buttons::CROSS);
+        assert!(held.is_pressed(Buttons::CROSS) && held.is_down(Buttons::CROSS));
+        assert!(held.released.is_empty());
+
+        let still_held = state(Buttons::CROSS | Buttons::UP, Buttons::CROSS);
+        assert_eq!(still_held.pressed, Buttons::UP);
+        assert!(!still_held.is_pressed(Buttons::CROSS));
+        assert!(still_held.is_down(Buttons::CROSS | Buttons::UP));
+
+        let released = state(Buttons::UP, Buttons::CROSS | Buttons::UP);
+        assert!(released.pressed.is_empty());
+        assert_eq!(released.released, Buttons::CROSS);
+        assert!(released.is_released(Buttons::CROSS | Buttons::CIRCLE));
+        assert!(!released.is_down(Buttons::CROSS));
+    }
+}
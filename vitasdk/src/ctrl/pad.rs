@@ -0,0 +1,152 @@
+use core::{ffi::c_int, mem::MaybeUninit};
+
+use vitasdk_sys::{
+    sceCtrlGetControllerPortInfo, sceCtrlGetSamplingMode, sceCtrlIsMultiControllerSupported,
+    sceCtrlPeekBufferPositive, sceCtrlPeekBufferPositive2, sceCtrlReadBufferPositive,
+    sceCtrlReadBufferPositive2, sceCtrlSetSamplingMode, sceCtrlSetSamplingModeExt, SceCtrlData,
+    SceCtrlPadInputMode, SceCtrlPortInfo, SCE_CTRL_MODE_ANALOG, SCE_CTRL_MODE_ANALOG_WIDE,
+    SCE_CTRL_MODE_DIGITAL,
+};
+
+use super::{Buttons, ControllerState, ControllerType, Port, Stick};
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceResult};
+
+/// Sets sampling mode of the built-in controller, analog sticks are only
+/// sampled in analog modes.
+#[doc(alias = "sceCtrlSetSamplingMode")]
+pub fn set_sampling_mode(mode: SamplingMode) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe { sceCtrlSetSamplingMode(mode.to_raw()) })
+}
+
+/// Sets sampling mode of the external controllers of PS TV.
+#[doc(alias = "sceCtrlSetSamplingModeExt")]
+pub fn set_sampling_mode_ext(mode: SamplingMode) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe { sceCtrlSetSamplingModeExt(mode.to_raw()) })
+}
+
+#[doc(alias = "sceCtrlGetSamplingMode")]
+pub fn sampling_mode() -> SceResult<SamplingMode> {
+    let mut mode = 0;
+    sce_result_unit_from_code(unsafe { sceCtrlGetSamplingMode(&mut mode) })?;
+    Ok(SamplingMode::from_raw(mode))
+}
+
+/// Returns whether the system supports several controllers, which is the
+/// case of PS TV.
+#[doc(alias = "sceCtrlIsMultiControllerSupported")]
+pub fn is_multi_controller_supported() -> bool {
+    unsafe { sceCtrlIsMultiControllerSupported() > 0 }
+}
+
+/// Returns type of the controller connected to each of the ports.
+#[doc(alias = "sceCtrlGetControllerPortInfo")]
+pub fn port_info() -> SceResult<[ControllerType; Port::COUNT]> {
+    let mut info = MaybeUninit::<SceCtrlPortInfo>::uninit();
+    sce_result_unit_from_code(unsafe { sceCtrlGetControllerPortInfo(info.as_mut_ptr()) })?;
+    let info = unsafe { info.assume_init() };
+    Ok(info.port.map(ControllerType::from_raw))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SamplingMode {
+    Digital,
+    Analog,
+    /// Analog sticks report the full range of values.
+    AnalogWide,
+}
+
+impl SamplingMode {
+    fn to_raw(self) -> SceCtrlPadInputMode {
+        match self {
+            SamplingMode::Digital => SCE_CTRL_MODE_DIGITAL,
+            SamplingMode::Analog => SCE_CTRL_MODE_ANALOG,
+            SamplingMode::AnalogWide => SCE_CTRL_MODE_ANALOG_WIDE,
+        }
+    }
+
+    fn from_raw(raw: SceCtrlPadInputMode) -> Self {
+        match raw {
+            SCE_CTRL_MODE_ANALOG => SamplingMode::Analog,
+            SCE_CTRL_MODE_ANALOG_WIDE => SamplingMode::AnalogWide,
+            _ => SamplingMode::Digital,
+        }
+    }
+}
+
+/// Reads controller of a port, keeping track of the buttons of the previous
+/// sample for edge detection.
+#[derive(Debug, Clone)]
+pub struct Controller {
+    port: Port,
+    deadzone: f32,
+    previous: Buttons,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Controller::new(Port::BUILT_IN)
+    }
+}
+
+impl Controller {
+    pub const DEFAULT_DEADZONE: f32 = 0.1;
+
+    pub fn new(port: Port) -> Self {
+        Controller {
+            port,
+            deadzone: Self::DEFAULT_DEADZONE,
+            previous: Buttons::empty(),
+        }
+    }
+
+    /// Sets the analog stick deadzone as a fraction of each axis range,
+    /// clamped between 0.0 and 0.99.
+    pub fn with_deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+        self
+    }
+
+    pub fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Returns the latest sample without waiting.
+    #[doc(alias = "sceCtrlPeekBufferPositive")]
+    #[doc(alias = "sceCtrlPeekBufferPositive2")]
+    pub fn peek(&mut self) -> SceResult<ControllerState> {
+        self.sample(match self.port {
+            Port::BUILT_IN => sceCtrlPeekBufferPositive,
+            _ => sceCtrlPeekBufferPositive2,
+        })
+    }
+
+    /// Waits for the next sample, which is taken once per vblank.
+    #[doc(alias = "sceCtrlReadBufferPositive")]
+    #[doc(alias = "sceCtrlReadBufferPositive2")]
+    pub fn read(&mut self) -> SceResult<ControllerState> {
+        self.sample(match self.port {
+            Port::BUILT_IN => sceCtrlReadBufferPositive,
+            _ => sceCtrlReadBufferPositive2,
+        })
+    }
+
+    fn sample(
+        &mut self,
+        f: unsafe extern "C" fn(c_int, *mut SceCtrlData, c_int) -> c_int,
+    ) -> SceResult<ControllerState> {
+        let mut data = MaybeUninit::<SceCtrlData>::zeroed();
+        sce_result_usize_from_code(unsafe { f(self.port.0 as c_int, data.as_mut_ptr(), 1) })?;
+        let data = unsafe { data.assume_init() };
+
+        let buttons = Buttons(data.buttons);
+        let state = ControllerState::new(
+            data.timeStamp,
+            buttons,
+            self.previous,
+            Stick::from_raw(data.lx, data.ly, self.deadzone),
+            Stick::from_raw(data.rx, data.ry, self.deadzone),
+        );
+        self.previous = buttons;
+        Ok(state)
+    }
+}
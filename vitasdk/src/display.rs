@@ -63,13 +63,40 @@ impl Display {
         sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitSetFrameBuf() })
     }
 
+    /// Same as [`Display::wait_set_framebuf`], but runs callbacks of the
+    /// calling thread while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceDisplayWaitSetFrameBufCB")]
+    pub fn wait_set_framebuf_cb(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitSetFrameBufCB() })
+    }
+
     pub fn wait_vblank_start(&self) -> SceResult<()> {
         sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitVblankStart() })
     }
 
+    /// Same as [`Display::wait_vblank_start`], but runs callbacks of the
+    /// calling thread while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceDisplayWaitVblankStartCB")]
+    pub fn wait_vblank_start_cb(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitVblankStartCB() })
+    }
+
     pub fn wait_vblank_start_multi(&self, vcount: u32) -> SceResult<()> {
         sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitVblankStartMulti(vcount) })
     }
+
+    /// Same as [`Display::wait_vblank_start_multi`], but runs callbacks of
+    /// the calling thread while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceDisplayWaitVblankStartMultiCB")]
+    pub fn wait_vblank_start_multi_cb(&self, vcount: u32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { vitasdk_sys::sceDisplayWaitVblankStartMultiCB(vcount) })
+    }
 }
 
 impl Drop for Display {
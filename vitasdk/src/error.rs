@@ -25,6 +25,14 @@ pub fn sce_result_uid_from_code(code: i32) -> SceResult<Uid> {
     }
 }
 
+#[track_caller]
+pub fn sce_result_usize_from_code(code: i32) -> SceResult<usize> {
+    match NonZeroI32::new(code) {
+        Some(code) if code.get() < 0 => Err(SceError::from_raw_error(code)),
+        _ => Ok(code as usize),
+    }
+}
+
 // TODO: Add consts
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct SceError(NonZeroI32);
@@ -37,6 +45,14 @@ impl SceError {
     pub fn code(&self) -> NonZeroI32 {
         self.0
     }
+
+    /// Builds an error out of one of the unsigned `SCE_*_ERROR_*` constants.
+    pub const fn from_error_code(code: u32) -> SceError {
+        match NonZeroI32::new(code as i32) {
+            Some(code) => SceError(code),
+            None => panic!("error code is zero"),
+        }
+    }
 }
 
 impl core::fmt::Display for SceError {
@@ -45,5 +61,5 @@ impl core::fmt::Display for SceError {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for SceError {}
+#[cfg(any(feature = "std", test))]
+impl core::error::Error for SceError {}
@@ -0,0 +1,492 @@
+//! Single-threaded async executor with a reactor over the `net` epoll API.
+//!
+//! Sockets are driven by [`AsyncTcpStream`](crate::net::AsyncTcpStream), and
+//! timers by [`sleep`]. HTTP requests stay blocking, as the system library
+//! bindings lack `sceHttpSetNonblock` and `sceHttpWaitRequest`.
+
+use alloc::{boxed::Box, collections::BTreeMap, rc::Rc, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    ffi::c_int,
+    future::Future,
+    mem,
+    pin::{pin, Pin},
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::sync::{Mutex, PoisonError};
+
+use vitasdk_sys::{
+    sceNetEpollAbort, sceNetEpollControl, sceNetEpollCreate, sceNetEpollDestroy, sceNetEpollWait,
+    SceNetEpollData, SceNetEpollEvent, SceNetEpollSystemData, SCE_NET_EPOLLERR, SCE_NET_EPOLLHUP,
+    SCE_NET_EPOLLIN, SCE_NET_EPOLLOUT, SCE_NET_EPOLL_ABORT_FLAG_PRESERVATION,
+    SCE_NET_EPOLL_CTL_ADD, SCE_NET_EPOLL_CTL_DEL, SCE_NET_EPOLL_CTL_MOD,
+};
+
+use crate::{
+    error::{sce_result_unit_from_code, sce_result_usize_from_code, SceResult},
+    time::Instant,
+};
+
+std::thread_local! {
+    static CURRENT: RefCell<Option<Rc<Shared>>> = const { RefCell::new(None) };
+}
+
+/// Runs `future` to completion on a new [`Executor`].
+pub fn block_on<F: Future>(future: F) -> SceResult<F::Output> {
+    Executor::new()?.block_on(future)
+}
+
+/// Spawns a task on the executor running the current task.
+///
+/// # Panics
+///
+/// Panics if called outside of [`Executor::block_on`].
+pub fn spawn(future: impl Future<Output = ()> + 'static) {
+    with_current(|shared| shared.spawned.borrow_mut().push(Box::pin(future)));
+}
+
+/// Completes after `duration` has elapsed, measured by the process clock.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now().saturating_add(duration),
+        timer: None,
+    }
+}
+
+/// Executor running the main future along with spawned tasks on the
+/// current thread.
+pub struct Executor {
+    shared: Rc<Shared>,
+}
+
+struct Shared {
+    reactor: Reactor,
+    ready: Arc<ReadyQueue>,
+    tasks: RefCell<Vec<Option<Task>>>,
+    spawned: RefCell<Vec<Task>>,
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Id of the future passed to [`Executor::block_on`].
+const MAIN_TASK: usize = usize::MAX;
+
+impl Executor {
+    pub fn new() -> SceResult<Self> {
+        let reactor = Reactor::new()?;
+        Ok(Executor {
+            shared: Rc::new(Shared {
+                ready: Arc::new(ReadyQueue {
+                    ids: Mutex::new(Vec::new()),
+                    eid: reactor.eid,
+                    aborted: AtomicBool::new(false),
+                }),
+                reactor,
+                tasks: RefCell::new(Vec::new()),
+                spawned: RefCell::new(Vec::new()),
+            }),
+        })
+    }
+
+    /// Spawns a task, which runs while the executor is blocked on a future.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.shared.spawned.borrow_mut().push(Box::pin(future));
+    }
+
+    /// Runs tasks until `future` completes.
+    ///
+    /// Unfinished spawned tasks are kept for the next call.
+    pub fn block_on<F: Future>(&self, future: F) -> SceResult<F::Output> {
+        let _current = CurrentGuard::enter(self.shared.clone());
+        let mut future = pin!(future);
+        let main_waker = self.shared.ready.waker(MAIN_TASK);
+        self.shared.ready.push(MAIN_TASK);
+        loop {
+            self.shared.start_spawned();
+            for id in self.shared.ready.take() {
+                if id == MAIN_TASK {
+                    let mut cx = Context::from_waker(&main_waker);
+                    if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                        return Ok(output);
+                    }
+                } else {
+                    self.shared.poll_task(id);
+                }
+            }
+            self.shared.start_spawned();
+            let timeout = match self.shared.ready.is_empty() {
+                true => self.shared.reactor.next_timeout(),
+                false => Some(Duration::ZERO),
+            };
+            if let Err(e) = self.shared.reactor.turn(timeout) {
+                // Waiting is aborted by wakers to wake up tasks from other threads
+                if !self.shared.ready.aborted.swap(false, Ordering::AcqRel) {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl core::fmt::Debug for Executor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Executor")
+            .field("eid", &self.shared.reactor.eid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Shared {
+    fn start_spawned(&self) {
+        let spawned = mem::take(&mut *self.spawned.borrow_mut());
+        let mut tasks = self.tasks.borrow_mut();
+        for task in spawned {
+            let id = match tasks.iter().position(Option::is_none) {
+                Some(id) => {
+                    tasks[id] = Some(task);
+                    id
+                }
+                None => {
+                    tasks.push(Some(task));
+                    tasks.len() - 1
+                }
+            };
+            self.ready.push(id);
+        }
+    }
+
+    fn poll_task(&self, id: usize) {
+        // Task is taken out, so that it could spawn other tasks
+        let Some(mut task) = self.tasks.borrow_mut().get_mut(id).and_then(Option::take) else {
+            return;
+        };
+        let waker = self.ready.waker(id);
+        if task
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_pending()
+        {
+            self.tasks.borrow_mut()[id] = Some(task);
+        }
+    }
+}
+
+/// Ids of the woken tasks, shared with their wakers.
+struct ReadyQueue {
+    ids: Mutex<Vec<usize>>,
+    eid: c_int,
+    /// Whether waiting of the reactor has been aborted since the last wait
+    aborted: AtomicBool,
+}
+
+impl ReadyQueue {
+    fn push(&self, id: usize) {
+        let mut ids = self.ids.lock().unwrap_or_else(PoisonError::into_inner);
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    fn take(&self) -> Vec<usize> {
+        mem::take(&mut *self.ids.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    fn waker(self: &Arc<Self>, id: usize) -> Waker {
+        Waker::from(Arc::new(TaskWaker {
+            id,
+            queue: self.clone(),
+        }))
+    }
+}
+
+struct TaskWaker {
+    id: usize,
+    queue: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.queue.push(self.id);
+        if !self.queue.aborted.swap(true, Ordering::AcqRel) {
+            // Preserved abort makes the next wait return, if not waiting right now
+            let _ = unsafe {
+                sceNetEpollAbort(self.queue.eid, SCE_NET_EPOLL_ABORT_FLAG_PRESERVATION as i32)
+            };
+        }
+    }
+}
+
+struct CurrentGuard {
+    previous: Option<Rc<Shared>>,
+}
+
+impl CurrentGuard {
+    fn enter(shared: Rc<Shared>) -> Self {
+        CurrentGuard {
+            previous: CURRENT.with(|current| current.borrow_mut().replace(shared)),
+        }
+    }
+}
+
+impl Drop for CurrentGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = self.previous.take());
+    }
+}
+
+fn with_current<R>(f: impl FnOnce(&Shared) -> R) -> R {
+    CURRENT.with(|current| {
+        f(current
+            .borrow()
+            .as_ref()
+            .expect("future must be polled within an executor"))
+    })
+}
+
+/// Runs `f` with the reactor of the current executor.
+///
+/// # Panics
+///
+/// Panics if called outside of [`Executor::block_on`].
+pub(crate) fn with_reactor<R>(f: impl FnOnce(&Reactor) -> R) -> R {
+    with_current(|shared| f(&shared.reactor))
+}
+
+/// Runs `f` with the reactor of the current executor, if there is one.
+pub(crate) fn try_with_reactor<R>(f: impl FnOnce(&Reactor) -> R) -> Option<R> {
+    CURRENT
+        .try_with(|current| current.borrow().as_ref().map(|shared| f(&shared.reactor)))
+        .ok()
+        .flatten()
+}
+
+/// Readiness a socket is waited for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interest {
+    Read,
+    Write,
+}
+
+/// Wakes tasks waiting for sockets and timers.
+pub(crate) struct Reactor {
+    eid: c_int,
+    sources: RefCell<BTreeMap<c_int, Source>>,
+    timers: RefCell<BTreeMap<(Instant, u64), Waker>>,
+    next_timer_id: Cell<u64>,
+}
+
+#[derive(Default)]
+struct Source {
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+    registered: bool,
+}
+
+impl Source {
+    fn events(&self) -> u32 {
+        let mut events = 0;
+        if self.reader.is_some() {
+            events |= SCE_NET_EPOLLIN;
+        }
+        if self.writer.is_some() {
+            events |= SCE_NET_EPOLLOUT;
+        }
+        events
+    }
+}
+
+impl Reactor {
+    fn new() -> SceResult<Self> {
+        let eid =
+            sce_result_usize_from_code(unsafe { sceNetEpollCreate(c"SceExecutor".as_ptr(), 0) })?;
+        Ok(Reactor {
+            eid: eid as c_int,
+            sources: RefCell::new(BTreeMap::new()),
+            timers: RefCell::new(BTreeMap::new()),
+            next_timer_id: Cell::new(0),
+        })
+    }
+
+    /// Wakes `waker` once the socket `id` is ready for `interest`.
+    pub(crate) fn register(&self, id: c_int, interest: Interest, waker: &Waker) -> SceResult<()> {
+        let mut sources = self.sources.borrow_mut();
+        let source = sources.entry(id).or_default();
+        let slot = match interest {
+            Interest::Read => &mut source.reader,
+            Interest::Write => &mut source.writer,
+        };
+        match slot {
+            Some(current) if current.will_wake(waker) => return Ok(()),
+            _ => *slot = Some(waker.clone()),
+        }
+        let op = match source.registered {
+            true => SCE_NET_EPOLL_CTL_MOD,
+            false => SCE_NET_EPOLL_CTL_ADD,
+        };
+        self.control(op, id, source.events())?;
+        source.registered = true;
+        Ok(())
+    }
+
+    /// Forgets the socket `id`, which must be done before it is closed.
+    pub(crate) fn deregister(&self, id: c_int) {
+        if let Some(source) = self.sources.borrow_mut().remove(&id) {
+            if source.registered {
+                let _ = self.control(SCE_NET_EPOLL_CTL_DEL, id, 0);
+            }
+        }
+    }
+
+    fn add_timer(&self, deadline: Instant, waker: Waker) -> (Instant, u64) {
+        let id = self.next_timer_id.get();
+        self.next_timer_id.set(id.wrapping_add(1));
+        self.timers.borrow_mut().insert((deadline, id), waker);
+        (deadline, id)
+    }
+
+    fn remove_timer(&self, key: (Instant, u64)) {
+        self.timers.borrow_mut().remove(&key);
+    }
+
+    /// Returns time until the nearest timer, or `None` without timers.
+    fn next_timeout(&self) -> Option<Duration> {
+        let timers = self.timers.borrow();
+        let (&(deadline, _), _) = timers.first_key_value()?;
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Waits for socket events for up to `timeout` with microsecond precision
+    /// and wakes the tasks waiting for them and for expired timers.
+    fn turn(&self, timeout: Option<Duration>) -> SceResult<()> {
+        let mut events = [const { empty_event() }; 16];
+        let timeout = timeout.map_or(-1, |timeout| {
+            timeout.as_micros().min(i32::MAX as u128) as c_int
+        });
+        let res = sce_result_usize_from_code(unsafe {
+            sceNetEpollWait(
+                self.eid,
+                events.as_mut_ptr(),
+                events.len() as c_int,
+                timeout,
+            )
+        });
+        if let Ok(n) = res {
+            for event in &events[..n] {
+                self.dispatch(event);
+            }
+        }
+        self.fire_timers();
+        res.map(|_| ())
+    }
+
+    fn dispatch(&self, event: &SceNetEpollEvent) {
+        let id = unsafe { event.data.fd };
+        let mut sources = self.sources.borrow_mut();
+        let Some(source) = sources.get_mut(&id) else {
+            return;
+        };
+        let error = event.events & (SCE_NET_EPOLLERR | SCE_NET_EPOLLHUP) != 0;
+        if error || event.events & SCE_NET_EPOLLIN != 0 {
+            if let Some(waker) = source.reader.take() {
+                waker.wake();
+            }
+        }
+        if error || event.events & SCE_NET_EPOLLOUT != 0 {
+            if let Some(waker) = source.writer.take() {
+                waker.wake();
+            }
+        }
+        // Level triggered events are only waited for while someone is interested
+        let events = source.events();
+        let _ = match events {
+            0 => self.control(SCE_NET_EPOLL_CTL_DEL, id, 0),
+            events => self.control(SCE_NET_EPOLL_CTL_MOD, id, events),
+        };
+        source.registered = events != 0;
+    }
+
+    fn fire_timers(&self) {
+        let now = Instant::now();
+        let mut timers = self.timers.borrow_mut();
+        let pending = timers.split_off(&(now.saturating_add(Duration::from_micros(1)), 0));
+        for waker in mem::replace(&mut *timers, pending).into_values() {
+            waker.wake();
+        }
+    }
+
+    fn control(&self, op: u32, id: c_int, events: u32) -> SceResult<()> {
+        let mut event = empty_event();
+        event.events = events;
+        event.data.fd = id;
+        sce_result_unit_from_code(unsafe {
+            sceNetEpollControl(self.eid, op as c_int, id, &mut event)
+        })
+    }
+}
+
+impl Drop for Reactor {
+    fn drop(&mut self) {
+        let _ = unsafe { sceNetEpollDestroy(self.eid) };
+    }
+}
+
+const fn empty_event() -> SceNetEpollEvent {
+    SceNetEpollEvent {
+        events: 0,
+        reserved: 0,
+        system: SceNetEpollSystemData { system: [0; 4] },
+        data: SceNetEpollData { u64_: 0 },
+    }
+}
+
+/// Future returned by [`sleep`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    deadline: Instant,
+    timer: Option<(Instant, u64)>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            if let Some(timer) = self.timer.take() {
+                with_reactor(|reactor| reactor.remove_timer(timer));
+            }
+            return Poll::Ready(());
+        }
+        let deadline = self.deadline;
+        let previous = self.timer.take();
+        self.timer = Some(with_reactor(|reactor| {
+            if let Some(timer) = previous {
+                reactor.remove_timer(timer);
+            }
+            reactor.add_timer(deadline, cx.waker().clone())
+        }));
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            // Executor could be gone if the future outlives it
+            try_with_reactor(|reactor| reactor.remove_timer(timer));
+        }
+    }
+}
@@ -1,54 +1,272 @@
-use std::{ffi::CStr, mem};
+#[cfg(any(feature = "alloc", test))]
+use alloc::{
+    boxed::Box,
+    ffi::CString,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{ffi::CStr, fmt, mem, ptr};
+#[cfg(any(feature = "alloc", test))]
+use core::{
+    ffi::{c_char, c_void},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+#[cfg(any(feature = "alloc", test))]
+use vitasdk_sys::SCE_HTTPS_ERROR_PROXY;
+use vitasdk_sys::{SceHttpMethods, SCE_HTTP_ERROR_NO_CONTENT_LENGTH};
 
-use vitasdk_sys::{
-    sceHttpCreateConnectionWithURL, sceHttpCreateRequest, sceHttpCreateRequestWithURL,
-    sceHttpCreateTemplate, sceHttpDeleteConnection, sceHttpDeleteRequest, sceHttpDeleteTemplate,
-    sceHttpInit, sceHttpTerm, SceHttpMethods,
+use self::sys::{
+    sceHttpAbortRequest, sceHttpCreateConnection, sceHttpCreateConnectionWithURL,
+    sceHttpCreateRequest, sceHttpCreateRequestWithURL, sceHttpCreateTemplate,
+    sceHttpDeleteConnection, sceHttpDeleteRequest, sceHttpDeleteTemplate,
+    sceHttpGetAllResponseHeaders, sceHttpGetResponseContentLength, sceHttpGetStatusCode,
+    sceHttpInit, sceHttpReadData, sceHttpSendRequest, sceHttpTerm, sceSslInit, sceSslTerm,
 };
 
+#[cfg(any(feature = "alloc", test))]
+use crate::proto::http::{invalid_url, origin};
+#[cfg(not(test))]
+use crate::sysmodule::{Module, ModuleId};
 use crate::{
-    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceResult},
-    module::{Module, ModuleId},
+    error::{
+        sce_result_uid_from_code, sce_result_unit_from_code, sce_result_usize_from_code, SceError,
+        SceResult,
+    },
     types::Uid,
 };
 
+#[cfg(any(feature = "alloc", test))]
+mod auth;
+mod body;
+#[cfg(any(feature = "alloc", test))]
+mod client;
+mod cookie;
+#[cfg(any(feature = "std", test))]
+mod download;
+mod https;
+mod settings;
+mod sys;
+mod template;
+
+#[cfg(any(feature = "alloc", test))]
+pub use auth::{basic_auth, AuthChallenge, AuthType, Credentials};
+pub use body::BodyWriter;
+#[cfg(any(feature = "alloc", test))]
+pub use client::{Client, RequestBuilder, Response};
+pub use cookie::add_cookie;
+#[cfg(any(feature = "std", test))]
+pub use cookie::CookieJar;
+#[cfg(any(feature = "std", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use download::{download, CancelToken, Download, Progress};
+pub use https::{
+    disable_option, enable_option, HttpsFlags, SslCert, SslCertName, SslCertNameEntry,
+    SslVerifyErrors,
+};
+#[cfg(any(feature = "alloc", test))]
+pub use https::{load_certs, LoadedCerts};
+pub use settings::HttpSettings;
+pub use template::TemplateBuilder;
+
 pub struct GlobalState {
+    // Fields are dropped in reverse initialization order
+    _http: HttpGuard,
+    _ssl: Option<SslGuard>,
+    #[cfg(not(test))]
     _module: Module,
 }
 
 impl GlobalState {
     pub fn new() -> SceResult<Self> {
+        GlobalStateOptions::new().init()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalStateOptions {
+    pool_size: u32,
+    ssl_pool_size: Option<u32>,
+}
+
+impl Default for GlobalStateOptions {
+    fn default() -> Self {
+        GlobalStateOptions::new()
+    }
+}
+
+impl GlobalStateOptions {
+    pub const DEFAULT_POOL_SIZE: u32 = 0x400000;
+    pub const DEFAULT_SSL_POOL_SIZE: u32 = 0x40000;
+
+    pub fn new() -> Self {
+        GlobalStateOptions {
+            pool_size: Self::DEFAULT_POOL_SIZE,
+            ssl_pool_size: Some(Self::DEFAULT_SSL_POOL_SIZE),
+        }
+    }
+
+    /// Sets size of the HTTP library memory pool.
+    pub fn with_pool_size(mut self, pool_size: u32) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    /// Sets size of the SSL library memory pool.
+    pub fn with_ssl_pool_size(mut self, ssl_pool_size: u32) -> Self {
+        self.ssl_pool_size = Some(ssl_pool_size);
+        self
+    }
+
+    /// Sets whether to initialize the SSL library required for HTTPS,
+    /// `true` by default.
+    pub fn with_ssl(mut self, ssl: bool) -> Self {
+        self.ssl_pool_size = match ssl {
+            true => Some(self.ssl_pool_size.unwrap_or(Self::DEFAULT_SSL_POOL_SIZE)),
+            false => None,
+        };
+        self
+    }
+
+    pub fn init(self) -> SceResult<GlobalState> {
+        #[cfg(not(test))]
         let module = Module::load(ModuleId::HTTPS)?;
-        sce_result_unit_from_code(unsafe { sceHttpInit(0x400000) })?;
-        Ok(GlobalState { _module: module })
+        let ssl = match self.ssl_pool_size {
+            Some(pool_size) => {
+                sce_result_unit_from_code(unsafe { sceSslInit(pool_size) })?;
+                Some(SslGuard { _private: () })
+            }
+            None => None,
+        };
+        sce_result_unit_from_code(unsafe { sceHttpInit(self.pool_size) })?;
+        Ok(GlobalState {
+            _http: HttpGuard { _private: () },
+            _ssl: ssl,
+            #[cfg(not(test))]
+            _module: module,
+        })
     }
 }
 
-impl Drop for GlobalState {
+/// Terminates the HTTP library on drop.
+struct HttpGuard {
+    _private: (),
+}
+
+impl Drop for HttpGuard {
     fn drop(&mut self) {
         let _ = unsafe { sceHttpTerm() };
     }
 }
 
+/// Terminates the SSL library on drop.
+struct SslGuard {
+    _private: (),
+}
+
+impl Drop for SslGuard {
+    fn drop(&mut self) {
+        let _ = unsafe { sceSslTerm() };
+    }
+}
+
+/// Callbacks registered on a template, kept alive by every connection and
+/// request inheriting them.
+#[derive(Clone, Default)]
+struct CallbackRefs {
+    #[cfg(any(feature = "alloc", test))]
+    refs: Vec<Arc<dyn Send + Sync>>,
+}
+
+impl fmt::Debug for CallbackRefs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackRefs").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug)]
 pub struct Template {
     uid: Uid,
+    callbacks: CallbackRefs,
+    #[cfg(any(feature = "alloc", test))]
+    client_options: ClientOptions,
+}
+
+/// Options of a [`Template`] implemented on top of the system library.
+#[cfg(any(feature = "alloc", test))]
+#[derive(Debug, Default)]
+struct ClientOptions {
+    proxy: Option<(CString, u16)>,
+    max_connections: Option<usize>,
 }
 
 impl Template {
-    pub fn new(user_agent: &CStr, http_ver: i32, auto_proxy_conf: i32) -> SceResult<Self> {
+    pub fn new(
+        user_agent: &CStr,
+        http_ver: HttpVersion,
+        auto_proxy_conf: AutoProxyConf,
+    ) -> SceResult<Self> {
         Ok(Template {
             uid: sce_result_uid_from_code(unsafe {
-                sceHttpCreateTemplate(user_agent.as_ptr(), http_ver, auto_proxy_conf)
+                sceHttpCreateTemplate(
+                    user_agent.as_ptr(),
+                    http_ver as i32,
+                    auto_proxy_conf.as_bool() as i32,
+                )
             })?,
+            callbacks: CallbackRefs::default(),
+            #[cfg(any(feature = "alloc", test))]
+            client_options: ClientOptions::default(),
         })
     }
 
+    pub fn builder<'a>() -> TemplateBuilder<'a> {
+        TemplateBuilder::new()
+    }
+
+    /// Creates connection to the server, or to the proxy server set with
+    /// [`TemplateBuilder::with_proxy`], which fails with
+    /// `SCE_HTTPS_ERROR_PROXY` for other schemes than `http`.
+    #[doc(alias = "sceHttpCreateConnection")]
+    pub fn create_connection(
+        &self,
+        server_name: &CStr,
+        scheme: &CStr,
+        port: u16,
+        keep_alive: KeepAlive,
+    ) -> SceResult<Connection> {
+        #[cfg(any(feature = "alloc", test))]
+        if let Some(proxy) = &self.client_options.proxy {
+            if scheme != c"http" {
+                return Err(SceError::from_error_code(SCE_HTTPS_ERROR_PROXY));
+            }
+            let server_name = server_name.to_str().map_err(|_| invalid_url())?;
+            let host = match port {
+                80 => server_name.to_string(),
+                port => format!("{server_name}:{port}"),
+            };
+            return self.create_proxy_connection(proxy, &host, keep_alive);
+        }
+        self.create_connection_(server_name, scheme, port, keep_alive)
+    }
+
+    /// Same as [`Template::create_connection`], taking the server from `url`.
+    #[doc(alias = "sceHttpCreateConnectionWithURL")]
     pub fn create_connection_with_url(
         &self,
         url: &CStr,
         keep_alive: KeepAlive,
     ) -> SceResult<Connection> {
+        #[cfg(any(feature = "alloc", test))]
+        if let Some(proxy) = &self.client_options.proxy {
+            let url = url.to_str().map_err(|_| invalid_url())?;
+            let host = origin(url)?
+                .strip_prefix("http://")
+                .ok_or(SceError::from_error_code(SCE_HTTPS_ERROR_PROXY))?;
+            return self.create_proxy_connection(proxy, host, keep_alive);
+        }
         Ok(Connection {
             uid: sce_result_uid_from_code(unsafe {
                 sceHttpCreateConnectionWithURL(
@@ -57,12 +275,63 @@ impl Template {
                     keep_alive.as_bool() as i32,
                 )
             })?,
+            callbacks: self.callbacks.clone(),
+            #[cfg(any(feature = "alloc", test))]
+            proxy_target: None,
+        })
+    }
+
+    /// Connects to the proxy server, which requests are forwarded to `host`
+    /// through.
+    #[cfg(any(feature = "alloc", test))]
+    fn create_proxy_connection(
+        &self,
+        (proxy, port): &(CString, u16),
+        host: &str,
+        keep_alive: KeepAlive,
+    ) -> SceResult<Connection> {
+        let target = ProxyTarget {
+            origin: format!("http://{host}"),
+            host: CString::new(host).map_err(|_| invalid_url())?,
+        };
+        let mut connection = self.create_connection_(proxy, c"http", *port, keep_alive)?;
+        connection.proxy_target = Some(target);
+        Ok(connection)
+    }
+
+    fn create_connection_(
+        &self,
+        server_name: &CStr,
+        scheme: &CStr,
+        port: u16,
+        keep_alive: KeepAlive,
+    ) -> SceResult<Connection> {
+        Ok(Connection {
+            uid: sce_result_uid_from_code(unsafe {
+                sceHttpCreateConnection(
+                    self.uid.get(),
+                    server_name.as_ptr(),
+                    scheme.as_ptr(),
+                    port,
+                    keep_alive.as_bool() as i32,
+                )
+            })?,
+            callbacks: self.callbacks.clone(),
+            #[cfg(any(feature = "alloc", test))]
+            proxy_target: None,
         })
     }
 
     /// Does the same thing as drop, but you could handle the error case.
     pub fn delete(self) -> SceResult<()> {
-        mem::ManuallyDrop::new(self).delete_()
+        let mut this = mem::ManuallyDrop::new(self);
+        let res = this.delete_();
+        this.callbacks = CallbackRefs::default();
+        #[cfg(any(feature = "alloc", test))]
+        {
+            this.client_options = ClientOptions::default();
+        }
+        res
     }
 
     fn delete_(&mut self) -> SceResult<()> {
@@ -79,48 +348,103 @@ impl Drop for Template {
 #[derive(Debug)]
 pub struct Connection {
     uid: Uid,
+    callbacks: CallbackRefs,
+    #[cfg(any(feature = "alloc", test))]
+    proxy_target: Option<ProxyTarget>,
+}
+
+/// Server requested through a proxy server.
+#[cfg(any(feature = "alloc", test))]
+#[derive(Debug)]
+struct ProxyTarget {
+    /// `http://host[:port]` prepended to the request paths.
+    origin: String,
+    host: CString,
 }
 
 impl Connection {
+    #[doc(alias = "sceHttpCreateRequest")]
     pub fn create_request(
         &self,
         method: Method,
         path: &CStr,
         content_length: u64,
     ) -> SceResult<Request> {
-        Ok(Request {
-            uid: sce_result_uid_from_code(unsafe {
-                sceHttpCreateRequest(
+        #[cfg(any(feature = "alloc", test))]
+        if let Some(target) = &self.proxy_target {
+            let mut url = Vec::from(target.origin.as_bytes());
+            url.extend_from_slice(path.to_bytes());
+            let url = CString::new(url).map_err(|_| invalid_url())?;
+            return self.create_proxy_request(target, method, &url, content_length);
+        }
+        self.create_request_(method, path, content_length)
+    }
+
+    #[doc(alias = "sceHttpCreateRequestWithURL")]
+    pub fn create_request_with_url(
+        &self,
+        method: Method,
+        url: &CStr,
+        content_length: u64,
+    ) -> SceResult<Request> {
+        #[cfg(any(feature = "alloc", test))]
+        if let Some(target) = &self.proxy_target {
+            return self.create_proxy_request(target, method, url, content_length);
+        }
+        Ok(Request::new(
+            sce_result_uid_from_code(unsafe {
+                sceHttpCreateRequestWithURL(
                     self.uid.get(),
                     method.0 as i32,
-                    path.as_ptr(),
+                    url.as_ptr(),
                     content_length,
                 )
             })?,
-        })
+            self.callbacks.clone(),
+        ))
     }
 
-    pub fn create_request_with_url(
+    #[cfg(any(feature = "alloc", test))]
+    fn create_proxy_request(
         &self,
+        target: &ProxyTarget,
         method: Method,
         url: &CStr,
         content_length: u64,
     ) -> SceResult<Request> {
-        Ok(Request {
-            uid: sce_result_uid_from_code(unsafe {
-                sceHttpCreateRequestWithURL(
+        // Request line of the absolute url is forwarded by the proxy
+        let request = self.create_request_(method, url, content_length)?;
+        request.set_header(c"Host", &target.host)?;
+        Ok(request)
+    }
+
+    fn create_request_(
+        &self,
+        method: Method,
+        path: &CStr,
+        content_length: u64,
+    ) -> SceResult<Request> {
+        Ok(Request::new(
+            sce_result_uid_from_code(unsafe {
+                sceHttpCreateRequest(
                     self.uid.get(),
                     method.0 as i32,
-                    url.as_ptr(),
+                    path.as_ptr(),
                     content_length,
                 )
             })?,
-        })
+            self.callbacks.clone(),
+        ))
     }
 
     /// Does the same thing as drop, but you could handle the error case.
     pub fn delete(self) -> SceResult<()> {
-        mem::ManuallyDrop::new(self).delete_()
+        let mut this = mem::ManuallyDrop::new(self);
+        let res = this.delete_();
+        this.callbacks = CallbackRefs::default();
+        #[cfg(any(feature = "alloc", test))]
+        drop(this.proxy_target.take());
+        res
     }
 
     fn delete_(&mut self) -> SceResult<()> {
@@ -134,18 +458,132 @@ impl Drop for Connection {
     }
 }
 
+#[derive(Debug)]
 pub struct Request {
     uid: Uid,
+    callbacks: CallbackRefs,
+    #[cfg(any(feature = "alloc", test))]
+    redirects_left: Option<Box<AtomicU32>>,
 }
 
 impl Request {
+    fn new(uid: Uid, callbacks: CallbackRefs) -> Self {
+        Request {
+            uid,
+            callbacks,
+            #[cfg(any(feature = "alloc", test))]
+            redirects_left: None,
+        }
+    }
+
+    /// Sends the request with the whole body, see [`Request::body_writer`]
+    /// to send it in parts.
+    #[doc(alias = "sceHttpSendRequest")]
     pub fn send(&self, post_data: &[u8]) -> SceResult<()> {
-        todo!()
+        sce_result_unit_from_code(unsafe {
+            sceHttpSendRequest(
+                self.uid.get(),
+                post_data.as_ptr().cast(),
+                post_data.len() as u32,
+            )
+        })
+    }
+
+    #[doc(alias = "sceHttpGetStatusCode")]
+    pub fn status_code(&self) -> SceResult<u16> {
+        let mut status_code = 0;
+        sce_result_unit_from_code(unsafe {
+            sceHttpGetStatusCode(self.uid.get(), &mut status_code)
+        })?;
+        Ok(status_code as u16)
+    }
+
+    /// Returns `None` if the response has no `Content-Length` header.
+    #[doc(alias = "sceHttpGetResponseContentLength")]
+    pub fn content_length(&self) -> SceResult<Option<u64>> {
+        let mut content_length = 0;
+        match sce_result_unit_from_code(unsafe {
+            sceHttpGetResponseContentLength(self.uid.get(), &mut content_length)
+        }) {
+            Ok(()) => Ok(Some(content_length)),
+            Err(e) if e == SceError::from_error_code(SCE_HTTP_ERROR_NO_CONTENT_LENGTH) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns raw response header, including the status line.
+    #[doc(alias = "sceHttpGetAllResponseHeaders")]
+    pub fn response_headers(&self) -> SceResult<&[u8]> {
+        let mut header = ptr::null_mut();
+        let mut header_size = 0;
+        sce_result_unit_from_code(unsafe {
+            sceHttpGetAllResponseHeaders(self.uid.get(), &mut header, &mut header_size)
+        })?;
+        if header.is_null() {
+            return Ok(&[]);
+        }
+        // SAFETY: header buffer is owned by the request
+        Ok(unsafe { core::slice::from_raw_parts(header.cast(), header_size as usize) })
+    }
+
+    /// Reads response body into `buf`, returning zero at the end of the body.
+    #[doc(alias = "sceHttpReadData")]
+    pub fn read(&self, buf: &mut [u8]) -> SceResult<usize> {
+        sce_result_usize_from_code(unsafe {
+            sceHttpReadData(self.uid.get(), buf.as_mut_ptr().cast(), buf.len() as u32)
+        })
+    }
+
+    /// Aborts request running on another thread.
+    #[doc(alias = "sceHttpAbortRequest")]
+    pub fn abort(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceHttpAbortRequest(self.uid.get()) })
+    }
+
+    /// Limits how many redirects are automatically followed by this request.
+    ///
+    /// Once the limit is reached the redirect response is returned as is.
+    #[cfg(any(feature = "alloc", test))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[doc(alias = "sceHttpSetRedirectCallback")]
+    pub fn set_max_redirects(&mut self, max: u32) -> SceResult<()> {
+        unsafe extern "C" fn callback(
+            _request: i32,
+            _status_code: i32,
+            _method: *mut i32,
+            _location: *const c_char,
+            user_arg: *mut c_void,
+        ) -> i32 {
+            let redirects_left = unsafe { &*user_arg.cast::<AtomicU32>() };
+            match redirects_left
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            {
+                Ok(_) => 0,
+                Err(_) => -1,
+            }
+        }
+
+        let redirects_left = Box::new(AtomicU32::new(max));
+        sce_result_unit_from_code(unsafe {
+            sys::sceHttpSetRedirectCallback(
+                self.uid.get(),
+                Some(callback),
+                (&*redirects_left as *const AtomicU32).cast_mut().cast(),
+            )
+        })?;
+        // Previous counter is no longer referenced by the callback
+        self.redirects_left = Some(redirects_left);
+        Ok(())
     }
 
     /// Does the same thing as drop, but you could handle the error case.
     pub fn delete(self) -> SceResult<()> {
-        mem::ManuallyDrop::new(self).delete_()
+        let mut this = mem::ManuallyDrop::new(self);
+        let res = this.delete_();
+        this.callbacks = CallbackRefs::default();
+        #[cfg(any(feature = "alloc", test))]
+        drop(this.redirects_left.take());
+        res
     }
 
     fn delete_(&mut self) -> SceResult<()> {
@@ -175,16 +613,41 @@ impl KeepAlive {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Method(SceHttpMethods::Type);
+#[derive(Default, Debug, Clone, Copy)]
+pub enum AutoProxyConf {
+    #[default]
+    Enable,
+    Disable,
+}
+
+impl AutoProxyConf {
+    pub const fn as_bool(self) -> bool {
+        match self {
+            AutoProxyConf::Enable => true,
+            AutoProxyConf::Disable => false,
+        }
+    }
+}
+
+#[non_exhaustive]
+#[repr(u32)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum HttpVersion {
+    V1_0 = vitasdk_sys::SCE_HTTP_VERSION_1_0,
+    #[default]
+    V1_1 = vitasdk_sys::SCE_HTTP_VERSION_1_1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Method(SceHttpMethods);
 
 impl Method {
-    pub const GET: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_GET);
-    pub const POST: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_POST);
-    pub const HEAD: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_HEAD);
-    pub const OPTIONS: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_OPTIONS);
-    pub const PUT: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_PUT);
-    pub const DELETE: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_DELETE);
-    pub const TRACE: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_TRACE);
-    pub const CONNECT: Self = Method(SceHttpMethods::SCE_HTTP_METHOD_CONNECT);
+    pub const GET: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_GET);
+    pub const POST: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_POST);
+    pub const HEAD: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_HEAD);
+    pub const OPTIONS: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_OPTIONS);
+    pub const PUT: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_PUT);
+    pub const DELETE: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_DELETE);
+    pub const TRACE: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_TRACE);
+    pub const CONNECT: Self = Method(vitasdk_sys::SCE_HTTP_METHOD_CONNECT);
 }
@@ -0,0 +1,167 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::ffi::{c_char, c_int, c_uchar, c_uint, c_void, CStr};
+
+use vitasdk_sys::{
+    SceHttpAuthType, SCE_HTTP_AUTH_BASIC, SCE_HTTP_AUTH_DIGEST, SCE_HTTP_PASSWORD_MAX_SIZE,
+    SCE_HTTP_USERNAME_MAX_SIZE,
+};
+
+use super::{sys::sceHttpSetAuthInfoCallback, Template};
+use crate::{
+    error::{sce_result_unit_from_code, SceResult},
+    proto::base64,
+};
+
+/// Returns value of `Authorization` header for the basic authentication scheme.
+pub fn basic_auth(username: &str, password: &str) -> String {
+    let mut credentials = Vec::with_capacity(username.len() + 1 + password.len());
+    credentials.extend_from_slice(username.as_bytes());
+    credentials.push(b':');
+    credentials.extend_from_slice(password.as_bytes());
+
+    let mut value = String::from("Basic ");
+    base64::encode(&credentials, &mut value);
+    value
+}
+
+/// Authentication scheme requested by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthType {
+    Basic,
+    Digest,
+    Other(SceHttpAuthType),
+}
+
+impl AuthType {
+    pub fn from_raw(raw: SceHttpAuthType) -> Self {
+        match raw {
+            SCE_HTTP_AUTH_BASIC => AuthType::Basic,
+            SCE_HTTP_AUTH_DIGEST => AuthType::Digest,
+            other => AuthType::Other(other),
+        }
+    }
+}
+
+/// Authentication request received from the server.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct AuthChallenge<'a> {
+    pub auth_type: AuthType,
+    pub realm: &'a CStr,
+}
+
+/// Credentials answering an [`AuthChallenge`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    username: String,
+    password: String,
+    save: bool,
+}
+
+impl Credentials {
+    /// Username and password are limited to 255 bytes.
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Credentials {
+            username: username.into(),
+            password: password.into(),
+            save: false,
+        }
+    }
+
+    /// Sets whether the HTTP library keeps credentials for later requests
+    /// to the same realm, `false` by default.
+    pub fn with_save(mut self, save: bool) -> Self {
+        self.save = save;
+        self
+    }
+}
+
+impl core::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("save", &self.save)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Template {
+    /// Sets a callback providing credentials when the server requires
+    /// authentication, which is inherited by connections and requests
+    /// created afterwards.
+    ///
+    /// Request fails with the server response if the callback returns `None`.
+    #[doc(alias = "sceHttpSetAuthInfoCallback")]
+    pub fn set_auth_callback<F>(&mut self, callback: F) -> SceResult<()>
+    where
+        F: Fn(&AuthChallenge<'_>) -> Option<Credentials> + Send + Sync + 'static,
+    {
+        #[allow(clippy::too_many_arguments)]
+        unsafe extern "C" fn trampoline<F>(
+            _request: c_int,
+            auth_type: SceHttpAuthType,
+            realm: *const c_char,
+            username: *mut c_char,
+            password: *mut c_char,
+            _need_entity: c_int,
+            _entity_body: *mut *mut c_uchar,
+            _entity_size: *mut c_uint,
+            save: *mut c_int,
+            user_arg: *mut c_void,
+        ) -> c_int
+        where
+            F: Fn(&AuthChallenge<'_>) -> Option<Credentials>,
+        {
+            let callback = unsafe { &*user_arg.cast::<F>() };
+            let challenge = AuthChallenge {
+                auth_type: AuthType::from_raw(auth_type),
+                realm: match realm.is_null() {
+                    true => c"",
+                    false => unsafe { CStr::from_ptr(realm) },
+                },
+            };
+            let Some(credentials) = callback(&challenge) else {
+                return -1;
+            };
+            let written = unsafe {
+                write_c_str(username, SCE_HTTP_USERNAME_MAX_SIZE, &credentials.username)
+                    && write_c_str(password, SCE_HTTP_PASSWORD_MAX_SIZE, &credentials.password)
+            };
+            if !written {
+                return -1;
+            }
+            if !save.is_null() {
+                unsafe { *save = credentials.save.into() };
+            }
+            0
+        }
+
+        let callback = Arc::new(callback);
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetAuthInfoCallback(
+                self.uid.get(),
+                Some(trampoline::<F>),
+                Arc::as_ptr(&callback).cast_mut().cast(),
+            )
+        })?;
+        self.callbacks.refs.push(callback);
+        Ok(())
+    }
+}
+
+/// Copies `s` into a nul-terminated buffer, failing if it doesn't fit or
+/// contains nul bytes.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `capacity` bytes.
+unsafe fn write_c_str(buf: *mut c_char, capacity: u32, s: &str) -> bool {
+    if buf.is_null() || s.len() >= capacity as usize || s.as_bytes().contains(&0) {
+        return false;
+    }
+    unsafe {
+        core::ptr::copy_nonoverlapping(s.as_ptr().cast(), buf, s.len());
+        *buf.add(s.len()) = 0;
+    }
+    true
+}
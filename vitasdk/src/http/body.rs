@@ -0,0 +1,126 @@
+use vitasdk_sys::{SCE_HTTP_ERROR_INVALID_VALUE, SCE_HTTP_ERROR_NOT_FOUND};
+
+use super::{sys::sceHttpSetRequestContentLength, HttpSettings, Request};
+use crate::{
+    error::{sce_result_unit_from_code, SceError, SceResult},
+    proto::http::chunk_size_line,
+};
+
+impl Request {
+    /// Overrides length of the body given on request creation.
+    #[doc(alias = "sceHttpSetRequestContentLength")]
+    pub fn set_content_length(&self, content_length: u64) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetRequestContentLength(self.uid.get(), content_length)
+        })
+    }
+
+    /// Starts sending body of known length in parts.
+    ///
+    /// Headers are sent along with the first part.
+    pub fn body_writer(&self, content_length: u64) -> SceResult<BodyWriter<'_>> {
+        self.set_content_length(content_length)?;
+        Ok(BodyWriter {
+            request: self,
+            remaining: Some(content_length),
+            started: false,
+        })
+    }
+
+    /// Starts sending body of unknown length using chunked transfer encoding.
+    ///
+    /// `Content-Length` header is removed, so the body is only framed by the
+    /// chunked encoding whatever length was given on request creation.
+    pub fn chunked_body_writer(&self) -> SceResult<BodyWriter<'_>> {
+        match self.remove_header(c"Content-Length") {
+            Err(e) if e != SceError::from_error_code(SCE_HTTP_ERROR_NOT_FOUND) => return Err(e),
+            _ => {}
+        }
+        self.set_header(c"Transfer-Encoding", c"chunked")?;
+        Ok(BodyWriter {
+            request: self,
+            remaining: None,
+            started: false,
+        })
+    }
+}
+
+/// Writer of a request body, created by [`Request::body_writer`] or
+/// [`Request::chunked_body_writer`].
+///
+/// Body must be completed with [`BodyWriter::finish`] before reading the response.
+#[derive(Debug)]
+#[must_use = "body is not complete until `finish` is called"]
+pub struct BodyWriter<'a> {
+    request: &'a Request,
+    /// Bytes left to send, or `None` for chunked transfer encoding
+    remaining: Option<u64>,
+    started: bool,
+}
+
+impl BodyWriter<'_> {
+    /// Sends a part of the body, blocking until it is written.
+    pub fn send(&mut self, data: &[u8]) -> SceResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        match &mut self.remaining {
+            Some(remaining) => {
+                if data.len() as u64 > *remaining {
+                    return Err(SceError::from_error_code(SCE_HTTP_ERROR_INVALID_VALUE));
+                }
+                self.request.send(data)?;
+                *remaining -= data.len() as u64;
+            }
+            None => {
+                let mut size = [0; 18];
+                self.request.send(chunk_size_line(&mut size, data.len()))?;
+                self.request.send(data)?;
+                self.request.send(b"\r\n")?;
+            }
+        }
+        self.started = true;
+        Ok(())
+    }
+
+    /// Sends a body read from `reader` until its end.
+    #[cfg(any(feature = "std", test))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn copy_from<R: std::io::Read>(&mut self, mut reader: R) -> std::io::Result<u64> {
+        let mut buf = [0; 0x1000];
+        let mut written = 0;
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(written),
+                Ok(n) => {
+                    self.send(&buf[..n]).map_err(std::io::Error::other)?;
+                    written += n as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Completes the body, failing if less than its length has been sent.
+    pub fn finish(self) -> SceResult<()> {
+        match self.remaining {
+            Some(0) if !self.started => self.request.send(&[]),
+            Some(0) => Ok(()),
+            Some(_) => Err(SceError::from_error_code(SCE_HTTP_ERROR_INVALID_VALUE)),
+            None => self.request.send(b"0\r\n\r\n"),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl std::io::Write for BodyWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.send(buf).map_err(std::io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
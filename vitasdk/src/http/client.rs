@@ -0,0 +1,558 @@
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    ffi::CString,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::cell::{Cell, RefCell};
+
+use vitasdk_sys::{SCE_HTTP_ERROR_INVALID_VALUE, SCE_HTTP_ERROR_PARSE_HTTP_INVALID_VALUE};
+
+use super::{
+    basic_auth, Connection, HttpSettings, KeepAlive, Method, Request, Template, TemplateBuilder,
+};
+use crate::{
+    error::{SceError, SceResult},
+    proto::http::{invalid_url, origin, parse_headers},
+};
+
+/// Blocking HTTP client, which keeps a connection alive per each origin.
+///
+/// Requires [`GlobalState`](super::GlobalState) to be initialized.
+#[derive(Debug)]
+pub struct Client {
+    connections: RefCell<BTreeMap<String, PooledConnection>>,
+    uses: Cell<u64>,
+    template: Arc<Template>,
+}
+
+#[derive(Debug)]
+struct PooledConnection {
+    connection: Arc<SharedConnection>,
+    last_use: u64,
+}
+
+/// Connection shared by the pool and responses of the requests created from
+/// it, so closing or evicting it doesn't delete it while a response is read.
+#[derive(Debug)]
+struct SharedConnection {
+    connection: Connection,
+    // Deleted after the connection
+    _template: Arc<Template>,
+}
+
+impl Client {
+    pub fn new() -> SceResult<Self> {
+        Ok(Client::from_template(TemplateBuilder::new().build()?))
+    }
+
+    /// Creates client out of a configured template.
+    pub fn from_template(template: Template) -> Self {
+        Client {
+            connections: RefCell::new(BTreeMap::new()),
+            uses: Cell::new(0),
+            template: Arc::new(template),
+        }
+    }
+
+    /// Template used for connections of this client, which could be used
+    /// to change its [`HttpSettings`].
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+
+    pub fn get(&self, url: &str) -> SceResult<Response> {
+        self.request(Method::GET, url).send()
+    }
+
+    pub fn post(&self, url: &str, body: &[u8]) -> SceResult<Response> {
+        self.request(Method::POST, url).body(body).send()
+    }
+
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            client: self,
+            method,
+            url: origin(url).and_then(|origin| {
+                Ok((
+                    origin.to_string(),
+                    CString::new(url).map_err(|_| invalid_url())?,
+                ))
+            }),
+            headers: Ok(Vec::new()),
+            body: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// Drops all of the kept alive connections, those used by unfinished
+    /// responses are closed once the responses are dropped.
+    pub fn close_connections(&self) {
+        self.connections.borrow_mut().clear();
+    }
+
+    fn create_request(
+        &self,
+        origin: &str,
+        method: Method,
+        url: &CString,
+        content_length: Option<u64>,
+    ) -> SceResult<(Request, Arc<SharedConnection>)> {
+        // Length of chunked bodies is left out, the body writer removing its header
+        let content_length = content_length.unwrap_or(0);
+
+        let mut connections = self.connections.borrow_mut();
+        if !connections.contains_key(origin) {
+            let connection = self
+                .template
+                .create_connection_with_url(url, KeepAlive::Enable)?;
+            if let Some(max_connections) = self.template.client_options.max_connections {
+                while connections.len() >= max_connections {
+                    // Connections of unfinished responses are evicted last
+                    let Some(lru) = connections
+                        .iter()
+                        .min_by_key(|(_, pooled)| {
+                            (Arc::strong_count(&pooled.connection) > 1, pooled.last_use)
+                        })
+                        .map(|(origin, _)| origin.clone())
+                    else {
+                        break;
+                    };
+                    connections.remove(&lru);
+                }
+            }
+            connections.insert(
+                origin.to_string(),
+                PooledConnection {
+                    connection: Arc::new(SharedConnection {
+                        connection,
+                        _template: Arc::clone(&self.template),
+                    }),
+                    last_use: 0,
+                },
+            );
+        }
+        let pooled = connections
+            .get_mut(origin)
+            .expect("connection of the origin was just inserted");
+        self.uses.set(self.uses.get() + 1);
+        pooled.last_use = self.uses.get();
+        let shared = Arc::clone(&pooled.connection);
+        drop(connections);
+        let request = shared
+            .connection
+            .create_request_with_url(method, url, content_length)?;
+        Ok((request, shared))
+    }
+
+    pub(super) fn forget_connection(&self, origin: &str) {
+        self.connections.borrow_mut().remove(origin);
+    }
+}
+
+/// Request being built by [`Client::request`].
+#[derive(Debug)]
+#[must_use = "request is not sent until `send` is called"]
+pub struct RequestBuilder<'a> {
+    client: &'a Client,
+    method: Method,
+    url: SceResult<(String, CString)>,
+    headers: SceResult<Vec<(CString, CString)>>,
+    body: Cow<'a, [u8]>,
+}
+
+impl<'a> RequestBuilder<'a> {
+    /// Adds a request header, keeping headers with the same name.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if let Ok(headers) = &mut self.headers {
+            match (CString::new(name), CString::new(value)) {
+                (Ok(name), Ok(value)) => headers.push((name, value)),
+                _ => {
+                    self.headers = Err(SceError::from_error_code(SCE_HTTP_ERROR_INVALID_VALUE));
+                }
+            }
+        }
+        self
+    }
+
+    /// Sets `Authorization` header for the basic authentication scheme.
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        self.header("Authorization", &basic_auth(username, password))
+    }
+
+    pub fn body(mut self, body: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sends the request, returning response with any status code.
+    pub fn send(self) -> SceResult<Response> {
+        let (origin, url) = self.url?;
+        let headers = self.headers?;
+        let (request, connection) =
+            self.client
+                .create_request(&origin, self.method, &url, Some(self.body.len() as u64))?;
+        for (name, value) in &headers {
+            request.add_header(name, value)?;
+        }
+        if let Err(e) = request.send(&self.body) {
+            // Connection could have been closed by the server
+            self.client.forget_connection(&origin);
+            return Err(e);
+        }
+        Response::new(request, connection)
+    }
+
+    /// Sends the request with body streamed from `reader`, ignoring the body
+    /// set with [`RequestBuilder::body`].
+    ///
+    /// Body of unknown length is sent with chunked transfer encoding.
+    #[cfg(any(feature = "std", test))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn send_reader<R: std::io::Read>(
+        self,
+        reader: R,
+        content_length: Option<u64>,
+    ) -> std::io::Result<Response> {
+        let (origin, url) = self.url.map_err(std::io::Error::other)?;
+        let headers = self.headers.map_err(std::io::Error::other)?;
+        let (request, connection) = self
+            .client
+            .create_request(&origin, self.method, &url, content_length)
+            .map_err(std::io::Error::other)?;
+        for (name, value) in &headers {
+            request
+                .add_header(name, value)
+                .map_err(std::io::Error::other)?;
+        }
+        let res = match content_length {
+            Some(content_length) => request.body_writer(content_length),
+            None => request.chunked_body_writer(),
+        }
+        .map_err(std::io::Error::other)
+        .and_then(|mut writer| {
+            writer.copy_from(reader)?;
+            writer.finish().map_err(std::io::Error::other)
+        });
+        if let Err(e) = res {
+            self.client.forget_connection(&origin);
+            return Err(e);
+        }
+        Response::new(request, connection).map_err(std::io::Error::other)
+    }
+}
+
+/// Response of a request sent through [`Client`].
+#[derive(Debug)]
+pub struct Response {
+    request: Request,
+    status: u16,
+    content_length: Option<u64>,
+    headers: Vec<u8>,
+    // Deleted after the request
+    _connection: Arc<SharedConnection>,
+}
+
+impl Response {
+    fn new(request: Request, connection: Arc<SharedConnection>) -> SceResult<Self> {
+        Ok(Response {
+            status: request.status_code()?,
+            content_length: request.content_length()?,
+            headers: request.response_headers()?.to_vec(),
+            request,
+            _connection: connection,
+        })
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Returns value of the first header with a matching case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn headers(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        parse_headers(&self.headers)
+    }
+
+    /// Underlying request, which could be used to read the body directly.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> SceResult<usize> {
+        self.request.read(buf)
+    }
+
+    pub fn into_bytes(mut self) -> SceResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        if let Some(content_length) = self.content_length {
+            bytes.reserve(content_length.min(0x100000) as usize);
+        }
+        let mut buf = [0; 0x1000];
+        loop {
+            match self.read(&mut buf)? {
+                0 => return Ok(bytes),
+                n => bytes.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    pub fn into_string(self) -> SceResult<String> {
+        String::from_utf8(self.into_bytes()?)
+            .map_err(|_| SceError::from_error_code(SCE_HTTP_ERROR_PARSE_HTTP_INVALID_VALUE))
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl std::io::Read for Response {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Response::read(self, buf).map_err(std::io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec::Vec};
+    use core::time::Duration;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread::{self, JoinHandle},
+    };
+
+    use vitasdk_sys::{SCE_HTTP_ERROR_TIMEOUT, SCE_HTTP_METHOD_GET};
+
+    use super::*;
+
+    /// Request received by the loopback server, with its body decoded.
+    struct Received {
+        head: String,
+        body: Vec<u8>,
+    }
+
+    fn read_line(reader: &mut impl BufRead) -> String {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+
+    fn read_request(reader: &mut impl BufRead) -> Received {
+        let mut head = String::new();
+        loop {
+            match read_line(reader) {
+                line if line.is_empty() => break,
+                line => head += &format!("{line}\n"),
+            }
+        }
+        let header = |name: &str| {
+            parse_headers(head.as_bytes())
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.to_string())
+        };
+        let mut body = Vec::new();
+        if header("Transfer-Encoding").as_deref() == Some("chunked") {
+            loop {
+                let size = usize::from_str_radix(&read_line(reader), 16).unwrap();
+                if size == 0 {
+                    assert_eq!(read_line(reader), "");
+                    break;
+                }
+                let start = body.len();
+                body.resize(start + size, 0);
+                reader.read_exact(&mut body[start..]).unwrap();
+                assert_eq!(read_line(reader), "");
+            }
+        } else if let Some(content_length) = header("Content-Length") {
+            body.resize(content_length.parse().unwrap(), 0);
+            reader.read_exact(&mut body).unwrap();
+        }
+        Received { head, body }
+    }
+
+    /// Answers connections accepted in order with `responses`, returning
+    /// origin of the server and the requests it received.
+    fn serve(responses: Vec<&'static str>) -> (String, JoinHandle<Vec<Received>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let mut reader = BufReader::new(listener.accept().unwrap().0);
+                    let request = read_request(&mut reader);
+                    reader.get_mut().write_all(response.as_bytes()).unwrap();
+                    request
+                })
+                .collect()
+        });
+        (origin, server)
+    }
+
+    #[test]
+    fn status_and_headers() {
+        let (origin, server) = serve(Vec::from([
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 5\r\nX-Reply: a\r\n\r\nnope!",
+        ]));
+        let client = Client::new().unwrap();
+        let response = client
+            .request(Method::GET, &format!("{origin}/missing?q=1"))
+            .header("X-Request", "b")
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(response.content_length(), Some(5));
+        assert_eq!(response.header("x-reply"), Some("a"));
+        assert_eq!(response.into_string().unwrap(), "nope!");
+
+        let requests = server.join().unwrap();
+        let head = &requests[0].head;
+        assert!(head.starts_with("GET /missing?q=1 HTTP/1.1\n"), "{head}");
+        assert!(
+            head.contains(&format!("Host: {}\n", &origin[7..])),
+            "{head}"
+        );
+        assert!(head.contains("X-Request: b\n"), "{head}");
+    }
+
+    #[test]
+    fn posts_body() {
+        let (origin, server) = serve(Vec::from(["HTTP/1.1 204 No Content\r\n\r\n"]));
+        let client = Client::new().unwrap();
+        let response = client.post(&format!("{origin}/"), b"hello").unwrap();
+        assert_eq!(response.status(), 204);
+        assert_eq!(response.into_bytes().unwrap(), b"");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.starts_with("POST / HTTP/1.1\n"));
+        assert!(requests[0].head.contains("Content-Length: 5\n"));
+        assert_eq!(requests[0].body, b"hello");
+    }
+
+    #[test]
+    fn follows_redirects() {
+        let (origin, server) = serve(Vec::from([
+            "HTTP/1.1 303 See Other\r\nLocation: /next\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone",
+        ]));
+        let client = Client::new().unwrap();
+        let response = client.post(&format!("{origin}/form"), b"a=1").unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.into_string().unwrap(), "done");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.starts_with("POST /form HTTP/1.1\n"));
+        assert_eq!(requests[0].body, b"a=1");
+        // See other is followed with a get request without body
+        assert!(requests[1].head.starts_with("GET /next HTTP/1.1\n"));
+        assert_eq!(requests[1].body, b"");
+    }
+
+    #[test]
+    fn limits_redirects() {
+        let (origin, server) = serve(Vec::from([
+            "HTTP/1.1 302 Found\r\nLocation: /a\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 302 Found\r\nLocation: /b\r\nContent-Length: 0\r\n\r\n",
+        ]));
+        let url = CString::new(format!("{origin}/")).unwrap();
+        let template = TemplateBuilder::new().build().unwrap();
+        let connection = template
+            .create_connection_with_url(&url, KeepAlive::Disable)
+            .unwrap();
+        let mut request = connection
+            .create_request_with_url(Method(SCE_HTTP_METHOD_GET), &url, 0)
+            .unwrap();
+        request.set_max_redirects(1).unwrap();
+        request.send(&[]).unwrap();
+        assert_eq!(request.status_code().unwrap(), 302);
+        let mut headers = parse_headers(request.response_headers().unwrap());
+        assert!(headers.any(|header| header == ("Location", "/b")));
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].head.starts_with("GET /a HTTP/1.1\n"));
+    }
+
+    #[test]
+    fn reads_chunked_body() {
+        let (origin, server) = serve(Vec::from([
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+             5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\nX-Trailer: 1\r\n\r\n",
+        ]));
+        let client = Client::new().unwrap();
+        let response = client.get(&format!("{origin}/")).unwrap();
+        assert_eq!(response.content_length(), None);
+        assert_eq!(response.into_string().unwrap(), "hello world");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn sends_chunked_body() {
+        let (origin, server) = serve(Vec::from([
+            "HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n",
+        ]));
+        let client = Client::new().unwrap();
+        let response = client
+            .request(Method::PUT, &format!("{origin}/upload"))
+            .send_reader(&b"streamed body"[..], None)
+            .unwrap();
+        assert_eq!(response.status(), 201);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].head.contains("Transfer-Encoding: chunked\n"));
+        assert!(!requests[0].head.contains("Content-Length"));
+        assert_eq!(requests[0].body, b"streamed body");
+    }
+
+    #[test]
+    fn forwards_through_proxy() {
+        let (origin, server) = serve(Vec::from(["HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"]));
+        let port = origin.rsplit_once(':').unwrap().1.parse().unwrap();
+        let template = TemplateBuilder::new()
+            .with_proxy(c"127.0.0.1", port)
+            .build()
+            .unwrap();
+        let client = Client::from_template(template);
+        let response = client.get("http://example.test:8080/page").unwrap();
+        assert_eq!(response.status(), 200);
+
+        let requests = server.join().unwrap();
+        let head = &requests[0].head;
+        assert!(
+            head.starts_with("GET http://example.test:8080/page HTTP/1.1\n"),
+            "{head}"
+        );
+        assert!(head.contains("Host: example.test:8080\n"), "{head}");
+    }
+
+    #[test]
+    fn times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let (done_tx, done_rx) = mpsc::channel();
+        let server = thread::spawn(move || {
+            let mut reader = BufReader::new(listener.accept().unwrap().0);
+            read_request(&mut reader);
+            // Keep the connection open without responding
+            done_rx.recv().unwrap();
+        });
+        let client = Client::new().unwrap();
+        client
+            .template()
+            .set_recv_timeout(Duration::from_millis(50))
+            .unwrap();
+        let error = client.get(&format!("{origin}/slow")).unwrap_err();
+        assert_eq!(error, SceError::from_error_code(SCE_HTTP_ERROR_TIMEOUT));
+        done_tx.send(()).unwrap();
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(any(feature = "std", test))]
+use alloc::{ffi::CString, sync::Arc};
+use core::ffi::CStr;
+#[cfg(any(feature = "std", test))]
+use core::ffi::{c_char, c_int, c_uint, c_void};
+#[cfg(any(feature = "std", test))]
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use super::sys::sceHttpAddCookie;
+#[cfg(any(feature = "std", test))]
+use super::{sys::sceHttpSetCookieRecvCallback, HttpSettings, Template};
+use crate::error::{sce_result_unit_from_code, SceResult};
+#[cfg(any(feature = "std", test))]
+use crate::proto::http::Cookies;
+
+/// Adds a cookie to the cookie store of the HTTP library, as if it was
+/// received in `Set-Cookie` header of a response from `url`.
+#[doc(alias = "sceHttpAddCookie")]
+pub fn add_cookie(url: &CStr, set_cookie: &[u8]) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe {
+        sceHttpAddCookie(
+            url.as_ptr(),
+            set_cookie.as_ptr().cast(),
+            set_cookie.len() as u32,
+        )
+    })
+}
+
+/// Copy of the received cookies, which could be saved to restore them
+/// in the cookie store of the HTTP library later.
+///
+/// Cookies are kept as received `Set-Cookie` headers, so the HTTP library
+/// remains responsible for their matching and expiration.
+#[cfg(any(feature = "std", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Mutex<Cookies>,
+}
+
+#[cfg(any(feature = "std", test))]
+impl CookieJar {
+    pub fn new() -> Self {
+        CookieJar::default()
+    }
+
+    /// Records a cookie, replacing a cookie with the same name received from the same url.
+    pub fn insert(&self, url: &str, set_cookie: &str) {
+        self.lock().insert(url, set_cookie);
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Adds all of the recorded cookies to the cookie store of the HTTP library.
+    pub fn restore(&self) -> SceResult<()> {
+        for (url, set_cookie) in self.lock().iter() {
+            let Ok(url) = CString::new(url) else {
+                continue;
+            };
+            add_cookie(&url, set_cookie.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes cookies as lines of url and `Set-Cookie` header separated by a tab.
+    pub fn save<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.lock().save(writer)
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.save(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads cookies written by [`CookieJar::save`], skipping malformed lines.
+    pub fn load<R: BufRead>(reader: R) -> io::Result<Self> {
+        Ok(CookieJar {
+            cookies: Mutex::new(Cookies::load(reader)?),
+        })
+    }
+
+    pub fn load_from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        CookieJar::load(BufReader::new(File::open(path)?))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cookies> {
+        self.cookies.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl Template {
+    /// Enables cookies and records all of them received by connections and
+    /// requests created afterwards into `jar`.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[doc(alias = "sceHttpSetCookieRecvCallback")]
+    pub fn set_cookie_jar(&mut self, jar: Arc<CookieJar>) -> SceResult<()> {
+        unsafe extern "C" fn callback(
+            _request: c_int,
+            url: *const c_char,
+            cookie_header: *const c_char,
+            header_len: c_uint,
+            user_arg: *mut c_void,
+        ) -> c_int {
+            let jar = unsafe { &*user_arg.cast::<CookieJar>() };
+            let url = unsafe { CStr::from_ptr(url) };
+            let header =
+                unsafe { core::slice::from_raw_parts(cookie_header.cast(), header_len as usize) };
+            if let (Ok(url), Ok(header)) = (url.to_str(), core::str::from_utf8(header)) {
+                for line in header.lines().filter(|line| !line.trim().is_empty()) {
+                    let set_cookie = match line.split_once(':') {
+                        Some((name, value)) if name.trim().eq_ignore_ascii_case("Set-Cookie") => {
+                            value
+                        }
+                        _ => line,
+                    };
+                    jar.insert(url, set_cookie);
+                }
+            }
+            0
+        }
+
+        self.set_cookie_enabled(true)?;
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetCookieRecvCallback(
+                self.uid.get(),
+                Some(callback),
+                Arc::as_ptr(&jar).cast_mut().cast(),
+            )
+        })?;
+        self.callbacks.refs.push(jar);
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use alloc::{boxed::Box, format, sync::Arc, vec};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use std::{
+    fs::OpenOptions,
+    io::{self, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::{Client, Method, Response};
+use crate::proto::http::{content_range_start, origin};
+
+/// Downloads `url` into the file at `path`, resuming a partially downloaded file.
+///
+/// Returns size of the downloaded file.
+pub fn download(url: &str, path: impl AsRef<Path>) -> io::Result<u64> {
+    Download::new(url).to_file(path)
+}
+
+/// Download of a single file with progress reporting, resume and cancellation.
+#[must_use = "nothing is downloaded until `to_file` is called"]
+pub struct Download<'a> {
+    url: &'a str,
+    client: Option<&'a Client>,
+    resume: bool,
+    progress: Option<Box<dyn FnMut(Progress) + 'a>>,
+    cancel_token: Option<CancelToken>,
+}
+
+impl<'a> Download<'a> {
+    pub fn new(url: &'a str) -> Self {
+        Download {
+            url,
+            client: None,
+            resume: true,
+            progress: None,
+            cancel_token: None,
+        }
+    }
+
+    /// Sets client to send the request with, a new one is created by default.
+    pub fn with_client(mut self, client: &'a Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Sets whether existing file is continued using a `Range` request,
+    /// `true` by default.
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Sets a callback called after each chunk of the body is written.
+    pub fn with_progress(mut self, progress: impl FnMut(Progress) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Sets a token checked between reads of the body.
+    ///
+    /// Cancelled download keeps the partial file, so it could be resumed later.
+    pub fn with_cancel_token(mut self, cancel_token: CancelToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    pub fn to_file(mut self, path: impl AsRef<Path>) -> io::Result<u64> {
+        let temp_client;
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                temp_client = Client::new().map_err(io::Error::other)?;
+                &temp_client
+            }
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        let mut received = match self.resume {
+            true => file.seek(SeekFrom::End(0))?,
+            false => 0,
+        };
+
+        let mut request = client.request(Method::GET, self.url);
+        if received != 0 {
+            request = request.header("Range", &format!("bytes={received}-"));
+        }
+        let mut response = request.send().map_err(io::Error::other)?;
+        let total = match response.status() {
+            206 if response
+                .header("Content-Range")
+                .and_then(content_range_start)
+                == Some(received) =>
+            {
+                response.content_length().map(|len| received + len)
+            }
+            206 => {
+                self.close_connection(client, &response);
+                return Err(io::Error::other(
+                    "content range doesn't start at the end of the file",
+                ));
+            }
+            // Server has ignored the range, so the file is started over
+            200 => {
+                received = 0;
+                response.content_length()
+            }
+            // Partial file is already complete
+            416 if received != 0 => {
+                self.close_connection(client, &response);
+                return Ok(received);
+            }
+            status => {
+                self.close_connection(client, &response);
+                return Err(io::Error::other(format!(
+                    "unexpected HTTP status code {status}"
+                )));
+            }
+        };
+        file.set_len(received)?;
+        file.seek(SeekFrom::Start(received))?;
+
+        let mut buf = vec![0; 0x10000];
+        loop {
+            self.report(received, total);
+            if self.is_cancelled() {
+                self.close_connection(client, &response);
+                return Err(io::Error::other("download cancelled"));
+            }
+            match response.read(&mut buf).map_err(io::Error::other)? {
+                0 => break,
+                n => {
+                    file.write_all(&buf[..n])?;
+                    received += n as u64;
+                }
+            }
+        }
+        file.flush()?;
+        Ok(received)
+    }
+
+    /// Closes connection of a response whose body is left unread, which
+    /// makes the connection unusable.
+    fn close_connection(&self, client: &Client, response: &Response) {
+        let _ = response.request().abort();
+        if let Ok(origin) = origin(self.url) {
+            client.forget_connection(origin);
+        }
+    }
+
+    fn report(&mut self, received: u64, total: Option<u64>) {
+        if let Some(progress) = &mut self.progress {
+            progress(Progress { received, total });
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+impl fmt::Debug for Download<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Download")
+            .field("url", &self.url)
+            .field("client", &self.client)
+            .field("resume", &self.resume)
+            .field("cancel_token", &self.cancel_token)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Progress of a [`Download`], including previously downloaded part of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub received: u64,
+    /// `None` if the server has not sent `Content-Length`
+    pub total: Option<u64>,
+}
+
+/// Token to cancel a [`Download`] from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,396 @@
+#[cfg(any(feature = "alloc", test))]
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+#[cfg(any(feature = "alloc", test))]
+use core::{
+    ffi::{c_int, c_uint, c_void},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use core::{fmt, marker::PhantomData, ops, ptr, slice};
+
+#[cfg(any(feature = "alloc", test))]
+use vitasdk_sys::{SceHttpsData, SCE_HTTP_ERROR_BUSY};
+use vitasdk_sys::{
+    SceHttpsFlag, SceHttpsSslErrorCode, SceRtcTick, SceSslCert, SceSslCertName,
+    SCE_HTTPS_ERROR_SSL_CN_CHECK, SCE_HTTPS_ERROR_SSL_INTERNAL, SCE_HTTPS_ERROR_SSL_INVALID_CERT,
+    SCE_HTTPS_ERROR_SSL_NOT_AFTER_CHECK, SCE_HTTPS_ERROR_SSL_NOT_BEFORE_CHECK,
+    SCE_HTTPS_ERROR_SSL_UNKNOWN_CA, SCE_HTTPS_FLAG_CLIENT_VERIFY, SCE_HTTPS_FLAG_CN_CHECK,
+    SCE_HTTPS_FLAG_KNOWN_CA_CHECK, SCE_HTTPS_FLAG_NOT_AFTER_CHECK, SCE_HTTPS_FLAG_NOT_BEFORE_CHECK,
+    SCE_HTTPS_FLAG_SERVER_VERIFY, SCE_SSL_ERROR_NOT_FOUND,
+};
+
+#[cfg(any(feature = "alloc", test))]
+use super::sys::{sceHttpsLoadCert, sceHttpsSetSslCallback, sceHttpsUnloadCert};
+#[cfg(any(feature = "alloc", test))]
+use super::Template;
+use super::{
+    sys::{
+        sceHttpsDisableOption, sceHttpsEnableOption, sceHttpsGetSslError, sceSslFreeSslCertName,
+        sceSslGetIssuerName, sceSslGetNameEntryCount, sceSslGetNameEntryInfo, sceSslGetNotAfter,
+        sceSslGetNotBefore, sceSslGetSerialNumber, sceSslGetSubjectName,
+    },
+    Request,
+};
+use crate::error::{sce_result_unit_from_code, SceError, SceResult};
+
+/// Enables verification flags for all of the HTTPS connections.
+#[doc(alias = "sceHttpsEnableOption")]
+pub fn enable_option(flags: HttpsFlags) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe { sceHttpsEnableOption(flags.0) })
+}
+
+/// Disables verification flags for all of the HTTPS connections.
+#[doc(alias = "sceHttpsDisableOption")]
+pub fn disable_option(flags: HttpsFlags) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe { sceHttpsDisableOption(flags.0) })
+}
+
+/// Certificate verification flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HttpsFlags(SceHttpsFlag);
+
+impl HttpsFlags {
+    pub const SERVER_VERIFY: Self = HttpsFlags(SCE_HTTPS_FLAG_SERVER_VERIFY);
+    pub const CLIENT_VERIFY: Self = HttpsFlags(SCE_HTTPS_FLAG_CLIENT_VERIFY);
+    pub const CN_CHECK: Self = HttpsFlags(SCE_HTTPS_FLAG_CN_CHECK);
+    pub const NOT_AFTER_CHECK: Self = HttpsFlags(SCE_HTTPS_FLAG_NOT_AFTER_CHECK);
+    pub const NOT_BEFORE_CHECK: Self = HttpsFlags(SCE_HTTPS_FLAG_NOT_BEFORE_CHECK);
+    pub const KNOWN_CA_CHECK: Self = HttpsFlags(SCE_HTTPS_FLAG_KNOWN_CA_CHECK);
+
+    pub const fn empty() -> Self {
+        HttpsFlags(0)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for HttpsFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        HttpsFlags(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for HttpsFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Errors found while verifying the server certificate chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SslVerifyErrors(SceHttpsSslErrorCode);
+
+impl SslVerifyErrors {
+    pub const INTERNAL: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_INTERNAL);
+    pub const INVALID_CERT: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_INVALID_CERT);
+    pub const CN_CHECK: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_CN_CHECK);
+    pub const NOT_AFTER_CHECK: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_NOT_AFTER_CHECK);
+    pub const NOT_BEFORE_CHECK: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_NOT_BEFORE_CHECK);
+    pub const UNKNOWN_CA: Self = SslVerifyErrors(SCE_HTTPS_ERROR_SSL_UNKNOWN_CA);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[cfg(any(feature = "alloc", test))]
+static CERTS_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Custom certificates loaded with [`load_certs`], unloaded on drop.
+///
+/// Only one set of certificates could be loaded at a time. The certificates
+/// are copied, so leaking this keeps them loaded without dangling pointers.
+#[cfg(any(feature = "alloc", test))]
+#[derive(Debug)]
+pub struct LoadedCerts {
+    _buffers: Vec<Box<[u8]>>,
+    _data: Vec<SceHttpsData>,
+    _ca_list: Vec<*const SceHttpsData>,
+}
+
+/// Loads CA certificates trusted in addition to the system ones, and an
+/// optional client certificate with its private key.
+///
+/// Fails with `SCE_HTTP_ERROR_BUSY` while other [`LoadedCerts`] are alive.
+#[cfg(any(feature = "alloc", test))]
+#[doc(alias = "sceHttpsLoadCert")]
+pub fn load_certs(
+    ca_certs: &[&[u8]],
+    cert: Option<&[u8]>,
+    priv_key: Option<&[u8]>,
+) -> SceResult<LoadedCerts> {
+    fn https_data(data: &[u8]) -> SceHttpsData {
+        SceHttpsData {
+            ptr: data.as_ptr().cast_mut().cast(),
+            size: data.len() as u32,
+        }
+    }
+
+    // Data stays referenced by the library until it is unloaded
+    let buffers: Vec<Box<[u8]>> = ca_certs
+        .iter()
+        .copied()
+        .chain(cert)
+        .chain(priv_key)
+        .map(Box::from)
+        .collect();
+    let data: Vec<SceHttpsData> = buffers.iter().map(|buffer| https_data(buffer)).collect();
+    let ca_list: Vec<*const SceHttpsData> = data[..ca_certs.len()]
+        .iter()
+        .map(|data| data as *const _)
+        .collect();
+    let mut extra = data[ca_certs.len()..].iter();
+    let cert = cert.map_or(ptr::null(), |_| extra.next().unwrap() as *const _);
+    let priv_key = priv_key.map_or(ptr::null(), |_| extra.next().unwrap() as *const _);
+    if CERTS_LOADED
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return Err(SceError::from_error_code(SCE_HTTP_ERROR_BUSY));
+    }
+    let res = sce_result_unit_from_code(unsafe {
+        sceHttpsLoadCert(
+            ca_list.len() as i32,
+            ca_list.as_ptr().cast_mut(),
+            cert,
+            priv_key,
+        )
+    });
+    if let Err(e) = res {
+        CERTS_LOADED.store(false, Ordering::Release);
+        return Err(e);
+    }
+    Ok(LoadedCerts {
+        _buffers: buffers,
+        _data: data,
+        _ca_list: ca_list,
+    })
+}
+
+#[cfg(any(feature = "alloc", test))]
+impl Drop for LoadedCerts {
+    fn drop(&mut self) {
+        let _ = unsafe { sceHttpsUnloadCert() };
+        CERTS_LOADED.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(any(feature = "alloc", test))]
+impl Template {
+    /// Sets a callback to inspect the server certificate chain, which is
+    /// inherited by connections and requests created afterwards.
+    ///
+    /// Handshake is continued only if the callback returns `true`.
+    #[doc(alias = "sceHttpsSetSslCallback")]
+    pub fn set_ssl_callback<F>(&mut self, callback: F) -> SceResult<()>
+    where
+        F: Fn(SslVerifyErrors, &[SslCert<'_>]) -> bool + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn trampoline<F>(
+            verify_errors: c_uint,
+            certs: *const *mut c_void,
+            cert_num: c_int,
+            arg: *mut c_void,
+        ) -> c_int
+        where
+            F: Fn(SslVerifyErrors, &[SslCert<'_>]) -> bool,
+        {
+            let callback = unsafe { &*arg.cast::<F>() };
+            let certs = match certs.is_null() || cert_num <= 0 {
+                true => &[],
+                // SAFETY: SslCert is a transparent wrapper of the pointer
+                false => unsafe { slice::from_raw_parts(certs.cast(), cert_num as usize) },
+            };
+            match callback(SslVerifyErrors(verify_errors), certs) {
+                true => 0,
+                false => -1,
+            }
+        }
+
+        let callback = Arc::new(callback);
+        sce_result_unit_from_code(unsafe {
+            sceHttpsSetSslCallback(
+                self.uid.get(),
+                Some(trampoline::<F>),
+                Arc::as_ptr(&callback).cast_mut().cast(),
+            )
+        })?;
+        // Previous callback is kept alive for connections still using it
+        self.callbacks.refs.push(callback);
+        Ok(())
+    }
+}
+
+impl Request {
+    /// Returns certificate verification errors of a failed request.
+    #[doc(alias = "sceHttpsGetSslError")]
+    pub fn ssl_verify_errors(&self) -> SceResult<SslVerifyErrors> {
+        let mut err_num = 0;
+        let mut detail = 0;
+        sce_result_unit_from_code(unsafe {
+            sceHttpsGetSslError(self.uid.get(), &mut err_num, &mut detail)
+        })?;
+        Ok(SslVerifyErrors(detail))
+    }
+}
+
+/// Certificate of a server certificate chain.
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct SslCert<'a> {
+    ptr: *mut SceSslCert,
+    _marker: PhantomData<&'a SceSslCert>,
+}
+
+impl<'a> SslCert<'a> {
+    #[doc(alias = "sceSslGetSubjectName")]
+    pub fn subject_name(&self) -> SceResult<SslCertName<'a>> {
+        SslCertName::from_ptr(unsafe { sceSslGetSubjectName(self.ptr) })
+    }
+
+    #[doc(alias = "sceSslGetIssuerName")]
+    pub fn issuer_name(&self) -> SceResult<SslCertName<'a>> {
+        SslCertName::from_ptr(unsafe { sceSslGetIssuerName(self.ptr) })
+    }
+
+    /// Returns start of the validity period in microseconds since 0001-01-01.
+    #[doc(alias = "sceSslGetNotBefore")]
+    pub fn not_before(&self) -> SceResult<u64> {
+        let mut tick = SceRtcTick { tick: 0 };
+        sce_result_unit_from_code(unsafe { sceSslGetNotBefore(self.ptr, &mut tick) })?;
+        Ok(tick.tick)
+    }
+
+    /// Returns end of the validity period in microseconds since 0001-01-01.
+    #[doc(alias = "sceSslGetNotAfter")]
+    pub fn not_after(&self) -> SceResult<u64> {
+        let mut tick = SceRtcTick { tick: 0 };
+        sce_result_unit_from_code(unsafe { sceSslGetNotAfter(self.ptr, &mut tick) })?;
+        Ok(tick.tick)
+    }
+
+    /// Returns serial number in big-endian byte order.
+    #[doc(alias = "sceSslGetSerialNumber")]
+    pub fn serial_number(&self) -> SceResult<&'a [u8]> {
+        let mut data = ptr::null();
+        let mut len = 0;
+        sce_result_unit_from_code(unsafe { sceSslGetSerialNumber(self.ptr, &mut data, &mut len) })?;
+        match data.is_null() {
+            true => Ok(&[]),
+            false => Ok(unsafe { slice::from_raw_parts(data.cast(), len as usize) }),
+        }
+    }
+}
+
+/// Subject or issuer name of a [`SslCert`], freed on drop.
+#[derive(Debug)]
+pub struct SslCertName<'a> {
+    ptr: *mut SceSslCertName,
+    _marker: PhantomData<&'a SceSslCert>,
+}
+
+impl SslCertName<'_> {
+    fn from_ptr(ptr: *mut SceSslCertName) -> SceResult<Self> {
+        match ptr.is_null() {
+            true => Err(SceError::from_error_code(SCE_SSL_ERROR_NOT_FOUND)),
+            false => Ok(SslCertName {
+                ptr,
+                _marker: PhantomData,
+            }),
+        }
+    }
+
+    #[doc(alias = "sceSslGetNameEntryCount")]
+    pub fn len(&self) -> usize {
+        unsafe { sceSslGetNameEntryCount(self.ptr) }.max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an entry, such as common name or organization.
+    #[doc(alias = "sceSslGetNameEntryInfo")]
+    pub fn entry(&self, index: usize) -> SceResult<SslCertNameEntry> {
+        let mut entry = SslCertNameEntry {
+            oid: [0; OID_CAPACITY],
+            value: [0; VALUE_CAPACITY],
+            value_len: 0,
+        };
+        let mut value_len = 0;
+        sce_result_unit_from_code(unsafe {
+            sceSslGetNameEntryInfo(
+                self.ptr,
+                index.min(i32::MAX as usize) as i32,
+                entry.oid.as_mut_ptr().cast(),
+                OID_CAPACITY as u32,
+                entry.value.as_mut_ptr().cast(),
+                VALUE_CAPACITY as u32,
+                &mut value_len,
+            )
+        })?;
+        entry.value_len = (value_len as usize).min(VALUE_CAPACITY);
+        Ok(entry)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = SceResult<SslCertNameEntry>> + '_ {
+        (0..self.len()).map(|index| self.entry(index))
+    }
+}
+
+impl Drop for SslCertName<'_> {
+    fn drop(&mut self) {
+        let _ = unsafe { sceSslFreeSslCertName(self.ptr) };
+    }
+}
+
+const OID_CAPACITY: usize = 64;
+const VALUE_CAPACITY: usize = 256;
+
+/// Entry of a [`SslCertName`] stored inline.
+#[derive(Clone)]
+pub struct SslCertNameEntry {
+    oid: [u8; OID_CAPACITY],
+    value: [u8; VALUE_CAPACITY],
+    value_len: usize,
+}
+
+impl SslCertNameEntry {
+    /// Returns name of the entry, such as `CN`.
+    pub fn oid(&self) -> &[u8] {
+        let len = self
+            .oid
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(OID_CAPACITY);
+        &self.oid[..len]
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value[..self.value_len]
+    }
+}
+
+impl fmt::Debug for SslCertNameEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SslCertNameEntry")
+            .field("oid", &format_args!("{}", self.oid().escape_ascii()))
+            .field("value", &format_args!("{}", self.value().escape_ascii()))
+            .finish()
+    }
+}
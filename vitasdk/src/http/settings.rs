@@ -0,0 +1,167 @@
+use core::{ffi::CStr, time::Duration};
+
+use vitasdk_sys::{SceHttpAddHeaderMode, SCE_HTTP_HEADER_ADD, SCE_HTTP_HEADER_OVERWRITE};
+
+use super::{
+    sys::{
+        sceHttpAddRequestHeader, sceHttpGetAuthEnabled, sceHttpGetAutoRedirect,
+        sceHttpGetCookieEnabled, sceHttpRemoveRequestHeader, sceHttpSetAuthEnabled,
+        sceHttpSetAutoRedirect, sceHttpSetConnectTimeOut, sceHttpSetCookieEnabled,
+        sceHttpSetRecvTimeOut, sceHttpSetResolveRetry, sceHttpSetResolveTimeOut,
+        sceHttpSetSendTimeOut,
+    },
+    Connection, Request, Template,
+};
+use crate::{
+    error::{sce_result_unit_from_code, SceResult},
+    types::Uid,
+};
+
+/// Settings shared by [`Template`], [`Connection`] and [`Request`].
+///
+/// Connections inherit settings of their template, and requests inherit
+/// settings of their connection.
+pub trait HttpSettings: http_settings_private::Sealed {
+    fn uid(&self) -> Uid;
+
+    /// Adds a request header, keeping headers with the same name.
+    #[doc(alias = "sceHttpAddRequestHeader")]
+    fn add_header(&self, name: &CStr, value: &CStr) -> SceResult<()> {
+        add_header(self.uid(), name, value, SCE_HTTP_HEADER_ADD)
+    }
+
+    /// Sets a request header, replacing headers with the same name.
+    #[doc(alias = "sceHttpAddRequestHeader")]
+    fn set_header(&self, name: &CStr, value: &CStr) -> SceResult<()> {
+        add_header(self.uid(), name, value, SCE_HTTP_HEADER_OVERWRITE)
+    }
+
+    #[doc(alias = "sceHttpRemoveRequestHeader")]
+    fn remove_header(&self, name: &CStr) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpRemoveRequestHeader(self.uid().get(), name.as_ptr())
+        })
+    }
+
+    /// Sets timeout with microsecond precision, 30 seconds by default.
+    #[doc(alias = "sceHttpSetConnectTimeOut")]
+    fn set_connect_timeout(&self, timeout: Duration) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetConnectTimeOut(self.uid().get(), timeout_to_usec(timeout))
+        })
+    }
+
+    /// Sets timeout with microsecond precision, 120 seconds by default.
+    #[doc(alias = "sceHttpSetSendTimeOut")]
+    fn set_send_timeout(&self, timeout: Duration) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetSendTimeOut(self.uid().get(), timeout_to_usec(timeout))
+        })
+    }
+
+    /// Sets timeout with microsecond precision, 120 seconds by default.
+    #[doc(alias = "sceHttpSetRecvTimeOut")]
+    fn set_recv_timeout(&self, timeout: Duration) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetRecvTimeOut(self.uid().get(), timeout_to_usec(timeout))
+        })
+    }
+
+    /// Sets timeout of a single DNS query with microsecond precision, 1 second by default.
+    #[doc(alias = "sceHttpSetResolveTimeOut")]
+    fn set_resolve_timeout(&self, timeout: Duration) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetResolveTimeOut(self.uid().get(), timeout_to_usec(timeout))
+        })
+    }
+
+    /// Sets how many times a DNS query is retried, 5 by default.
+    #[doc(alias = "sceHttpSetResolveRetry")]
+    fn set_resolve_retry(&self, retry: u32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetResolveRetry(self.uid().get(), retry.min(i32::MAX as u32) as i32)
+        })
+    }
+
+    /// Sets whether redirects are followed automatically, enabled by default.
+    #[doc(alias = "sceHttpSetAutoRedirect")]
+    fn set_auto_redirect(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetAutoRedirect(self.uid().get(), enable.into())
+        })
+    }
+
+    #[doc(alias = "sceHttpGetAutoRedirect")]
+    fn auto_redirect(&self) -> SceResult<bool> {
+        let mut enable = 0;
+        sce_result_unit_from_code(unsafe {
+            sceHttpGetAutoRedirect(self.uid().get(), &mut enable)
+        })?;
+        Ok(enable != 0)
+    }
+
+    /// Sets whether cookies are stored and sent.
+    #[doc(alias = "sceHttpSetCookieEnabled")]
+    fn set_cookie_enabled(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceHttpSetCookieEnabled(self.uid().get(), enable.into())
+        })
+    }
+
+    #[doc(alias = "sceHttpGetCookieEnabled")]
+    fn cookie_enabled(&self) -> SceResult<bool> {
+        let mut enable = 0;
+        sce_result_unit_from_code(unsafe {
+            sceHttpGetCookieEnabled(self.uid().get(), &mut enable)
+        })?;
+        Ok(enable != 0)
+    }
+
+    /// Sets whether authentication requests of the server are answered.
+    #[doc(alias = "sceHttpSetAuthEnabled")]
+    fn set_auth_enabled(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceHttpSetAuthEnabled(self.uid().get(), enable.into()) })
+    }
+
+    #[doc(alias = "sceHttpGetAuthEnabled")]
+    fn auth_enabled(&self) -> SceResult<bool> {
+        let mut enable = 0;
+        sce_result_unit_from_code(unsafe { sceHttpGetAuthEnabled(self.uid().get(), &mut enable) })?;
+        Ok(enable != 0)
+    }
+}
+
+impl HttpSettings for Template {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+}
+
+impl HttpSettings for Connection {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+}
+
+impl HttpSettings for Request {
+    fn uid(&self) -> Uid {
+        self.uid
+    }
+}
+
+mod http_settings_private {
+    pub trait Sealed {}
+    impl Sealed for super::Template {}
+    impl Sealed for super::Connection {}
+    impl Sealed for super::Request {}
+}
+
+fn add_header(uid: Uid, name: &CStr, value: &CStr, mode: SceHttpAddHeaderMode) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe {
+        sceHttpAddRequestHeader(uid.get(), name.as_ptr(), value.as_ptr(), mode)
+    })
+}
+
+fn timeout_to_usec(timeout: Duration) -> u32 {
+    timeout.as_micros().min(u32::MAX as u128) as u32
+}
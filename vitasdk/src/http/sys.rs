@@ -0,0 +1,30 @@
+//! Functions of the HTTP and SSL libraries, replaced in host tests by a
+//! loopback implementation on top of `std::net`.
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+pub(super) use self::mock::*;
+#[cfg(all(not(test), feature = "std"))]
+pub(super) use vitasdk_sys::sceHttpSetCookieRecvCallback;
+#[cfg(not(test))]
+pub(super) use vitasdk_sys::{
+    sceHttpAbortRequest, sceHttpAddCookie, sceHttpAddRequestHeader, sceHttpCreateConnection,
+    sceHttpCreateConnectionWithURL, sceHttpCreateRequest, sceHttpCreateRequestWithURL,
+    sceHttpCreateTemplate, sceHttpDeleteConnection, sceHttpDeleteRequest, sceHttpDeleteTemplate,
+    sceHttpGetAllResponseHeaders, sceHttpGetAuthEnabled, sceHttpGetAutoRedirect,
+    sceHttpGetCookieEnabled, sceHttpGetResponseContentLength, sceHttpGetStatusCode, sceHttpInit,
+    sceHttpReadData, sceHttpRemoveRequestHeader, sceHttpSendRequest, sceHttpSetAuthEnabled,
+    sceHttpSetAutoRedirect, sceHttpSetConnectTimeOut, sceHttpSetCookieEnabled,
+    sceHttpSetRecvTimeOut, sceHttpSetRequestContentLength, sceHttpSetResolveRetry,
+    sceHttpSetResolveTimeOut, sceHttpSetSendTimeOut, sceHttpTerm, sceHttpsDisableOption,
+    sceHttpsEnableOption, sceHttpsGetSslError, sceSslFreeSslCertName, sceSslGetIssuerName,
+    sceSslGetNameEntryCount, sceSslGetNameEntryInfo, sceSslGetNotAfter, sceSslGetNotBefore,
+    sceSslGetSerialNumber, sceSslGetSubjectName, sceSslInit, sceSslTerm,
+};
+#[cfg(all(not(test), feature = "alloc"))]
+pub(super) use vitasdk_sys::{
+    sceHttpSetAuthInfoCallback, sceHttpSetRedirectCallback, sceHttpsLoadCert,
+    sceHttpsSetSslCallback, sceHttpsUnloadCert,
+};
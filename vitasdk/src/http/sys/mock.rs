@@ -0,0 +1,939 @@
+//! Loopback implementation of the HTTP library used by host tests.
+//!
+//! Requests are sent as plain HTTP/1.1 over a new `std::net` connection,
+//! following redirects and decoding chunked bodies the way the system library
+//! does. HTTPS, cookies and authentication callbacks are accepted but not
+//! emulated.
+
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+use alloc::{
+    collections::BTreeMap,
+    ffi::CString,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    ffi::{c_char, c_int, c_uint, c_ulonglong, c_ushort, c_void, CStr},
+    ptr, slice, str,
+    sync::atomic::{AtomicBool, AtomicI32, Ordering},
+    time::Duration,
+};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use vitasdk_sys::{
+    SceHttpAddHeaderMode, SceHttpAuthInfoCallback, SceHttpCookieRecvCallback,
+    SceHttpRedirectCallback, SceHttpsCallback, SceHttpsData, SceRtcTick, SceSslCert,
+    SceSslCertName, SCE_HTTP_ERROR_ABORTED, SCE_HTTP_ERROR_AFTER_SEND, SCE_HTTP_ERROR_BAD_RESPONSE,
+    SCE_HTTP_ERROR_BEFORE_SEND, SCE_HTTP_ERROR_INVALID_ID, SCE_HTTP_ERROR_INVALID_URL,
+    SCE_HTTP_ERROR_INVALID_VALUE, SCE_HTTP_ERROR_NETWORK, SCE_HTTP_ERROR_NOT_FOUND,
+    SCE_HTTP_ERROR_NO_CONTENT_LENGTH, SCE_HTTP_ERROR_TIMEOUT, SCE_HTTP_ERROR_UNKNOWN_METHOD,
+    SCE_HTTP_ERROR_UNKNOWN_SCHEME, SCE_HTTP_HEADER_OVERWRITE, SCE_HTTP_METHOD_CONNECT,
+    SCE_HTTP_METHOD_DELETE, SCE_HTTP_METHOD_GET, SCE_HTTP_METHOD_HEAD, SCE_HTTP_METHOD_OPTIONS,
+    SCE_HTTP_METHOD_POST, SCE_HTTP_METHOD_PUT, SCE_HTTP_METHOD_TRACE, SCE_SSL_ERROR_NOT_FOUND,
+};
+
+use crate::proto::http::parse_headers;
+
+/// Redirects followed by a request before the redirect response is returned.
+const MAX_REDIRECTS: u32 = 10;
+
+static OBJECTS: Mutex<BTreeMap<c_int, Object>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicI32 = AtomicI32::new(1);
+
+enum Object {
+    Template(Settings),
+    Connection(Settings, String, u16),
+    Request(Arc<MockRequest>),
+}
+
+/// Settings inherited from templates by connections, and from connections by
+/// requests.
+#[derive(Clone)]
+struct Settings {
+    headers: Vec<(String, String)>,
+    auto_redirect: bool,
+    auth_enabled: bool,
+    cookie_enabled: bool,
+    connect_timeout: Option<Duration>,
+    send_timeout: Option<Duration>,
+    recv_timeout: Option<Duration>,
+    /// Callback with its user argument stored as an address
+    redirect_callback: (SceHttpRedirectCallback, usize),
+}
+
+impl Settings {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn remove_header(&mut self, name: &str) -> bool {
+        let len = self.headers.len();
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.len() != len
+    }
+}
+
+struct MockRequest {
+    state: Mutex<RequestState>,
+    /// Clone of the socket, shut down to abort a request blocked on it
+    socket: Mutex<Option<TcpStream>>,
+    aborted: AtomicBool,
+}
+
+impl MockRequest {
+    fn state(&self) -> MutexGuard<'_, RequestState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn socket(&self) -> MutexGuard<'_, Option<TcpStream>> {
+        self.socket.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn error_code(&self, error: io::Error) -> c_int {
+        if self.aborted.load(Ordering::Relaxed) {
+            return SCE_HTTP_ERROR_ABORTED as c_int;
+        }
+        (match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => SCE_HTTP_ERROR_TIMEOUT,
+            io::ErrorKind::InvalidData => SCE_HTTP_ERROR_BAD_RESPONSE,
+            _ => SCE_HTTP_ERROR_NETWORK,
+        }) as c_int
+    }
+}
+
+struct RequestState {
+    id: c_int,
+    settings: Settings,
+    method: c_int,
+    host: String,
+    port: u16,
+    target: String,
+    /// Body sent so far, sent again on redirects keeping the method
+    body: Vec<u8>,
+    stream: Option<BufReader<TcpStream>>,
+    response: Option<Response>,
+}
+
+struct Response {
+    status: c_int,
+    /// Status line and header lines
+    headers: Vec<u8>,
+    content_length: Option<u64>,
+    body: Body,
+}
+
+enum Body {
+    Length(u64),
+    Chunked { remaining: u64, done: bool },
+    UntilClose,
+}
+
+impl RequestState {
+    fn connect(&mut self, request: &MockRequest) -> io::Result<()> {
+        let addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or(io::ErrorKind::NotFound)?;
+        let stream = match self.settings.connect_timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout)?,
+            None => TcpStream::connect(addr)?,
+        };
+        stream.set_write_timeout(self.settings.send_timeout)?;
+        stream.set_read_timeout(self.settings.recv_timeout)?;
+        *request.socket() = Some(stream.try_clone()?);
+        if request.aborted.load(Ordering::Relaxed) {
+            stream.shutdown(Shutdown::Both)?;
+        }
+
+        let mut head = format!("{} {} HTTP/1.1\r\n", method_name(self.method)?, self.target);
+        if self.settings.header("Host").is_none() {
+            match self.port {
+                80 => head += &format!("Host: {}\r\n", self.host),
+                port => head += &format!("Host: {}:{port}\r\n", self.host),
+            }
+        }
+        for (name, value) in &self.settings.headers {
+            head += &format!("{name}: {value}\r\n");
+        }
+        head += "\r\n";
+        (&stream).write_all(head.as_bytes())?;
+        self.stream = Some(BufReader::new(stream));
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.stream
+            .as_mut()
+            .expect("request is connected before writing")
+            .get_mut()
+            .write_all(data)
+    }
+
+    fn body_complete(&self, data: &[u8]) -> bool {
+        match self.settings.header("Transfer-Encoding") {
+            // Last chunk is sent by itself by the body writer
+            Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => data == b"0\r\n\r\n",
+            _ => {
+                let content_length = self.settings.header("Content-Length");
+                self.body.len() as u64 >= content_length.map_or(Ok(0), str::parse).unwrap_or(0)
+            }
+        }
+    }
+
+    fn read_response(&mut self, request: &MockRequest) -> io::Result<()> {
+        let mut redirects = 0;
+        loop {
+            let stream = self.stream.as_mut().expect("request is connected");
+            let response = read_head(stream, self.method == SCE_HTTP_METHOD_HEAD as c_int)?;
+            let location = parse_headers(&response.headers)
+                .find(|(name, _)| name.eq_ignore_ascii_case("Location"))
+                .map(|(_, value)| value.to_string());
+            let redirect = matches!(response.status, 301 | 302 | 303 | 307 | 308);
+            match location {
+                Some(location)
+                    if redirect && self.settings.auto_redirect && redirects < MAX_REDIRECTS =>
+                {
+                    if !self.redirect(response.status, &location)? {
+                        self.response = Some(response);
+                        return Ok(());
+                    }
+                    redirects += 1;
+                    self.connect(request)?;
+                    let body = core::mem::take(&mut self.body);
+                    self.write(&body)?;
+                    self.body = body;
+                }
+                _ => {
+                    self.response = Some(response);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Moves the request to `location`, returning `false` if the redirect
+    /// callback refused it.
+    fn redirect(&mut self, status: c_int, location: &str) -> io::Result<bool> {
+        let mut method = self.method;
+        if let (Some(callback), user_arg) = self.settings.redirect_callback {
+            let location = CString::new(location).map_err(|_| io::ErrorKind::InvalidData)?;
+            let res = unsafe {
+                callback(
+                    self.id,
+                    status,
+                    &mut method,
+                    location.as_ptr(),
+                    user_arg as *mut c_void,
+                )
+            };
+            if res != 0 {
+                return Ok(false);
+            }
+        }
+        if status == 303 || (matches!(status, 301 | 302) && method == SCE_HTTP_METHOD_POST as c_int)
+        {
+            method = SCE_HTTP_METHOD_GET as c_int;
+            self.body.clear();
+            self.settings.remove_header("Content-Length");
+            self.settings.remove_header("Transfer-Encoding");
+        }
+        self.method = method;
+        match location.starts_with('/') {
+            true => self.target = location.to_string(),
+            false => {
+                let (host, port, target) = parse_url(location).ok_or(io::ErrorKind::InvalidData)?;
+                self.settings.remove_header("Host");
+                (self.host, self.port, self.target) = (host, port, target);
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_body(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (Some(stream), Some(response)) = (&mut self.stream, &mut self.response) else {
+            return Err(io::ErrorKind::NotConnected.into());
+        };
+        match &mut response.body {
+            Body::Length(remaining) => {
+                let len = (*remaining).min(buf.len() as u64) as usize;
+                if len == 0 {
+                    return Ok(0);
+                }
+                match stream.read(&mut buf[..len])? {
+                    0 => Err(io::ErrorKind::UnexpectedEof.into()),
+                    n => {
+                        *remaining -= n as u64;
+                        Ok(n)
+                    }
+                }
+            }
+            Body::Chunked { remaining, done } => {
+                while *remaining == 0 {
+                    if *done {
+                        return Ok(0);
+                    }
+                    let line = read_line(stream)?;
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    *remaining = u64::from_str_radix(size, 16)
+                        .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+                    if *remaining == 0 {
+                        // Skip the trailer
+                        while !read_line(stream)?.is_empty() {}
+                        *done = true;
+                    }
+                }
+                let len = (*remaining).min(buf.len() as u64) as usize;
+                let n = stream.read(&mut buf[..len])?;
+                if n == 0 && len != 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                *remaining -= n as u64;
+                if *remaining == 0 && !read_line(stream)?.is_empty() {
+                    return Err(io::ErrorKind::InvalidData.into());
+                }
+                Ok(n)
+            }
+            Body::UntilClose => stream.read(buf),
+        }
+    }
+}
+
+fn read_line(stream: &mut BufReader<TcpStream>) -> io::Result<String> {
+    let mut line = String::new();
+    if stream.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn read_head(stream: &mut BufReader<TcpStream>, head_request: bool) -> io::Result<Response> {
+    let mut headers = Vec::new();
+    loop {
+        let start = headers.len();
+        if stream.read_until(b'\n', &mut headers)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if matches!(&headers[start..], b"\r\n" | b"\n") {
+            headers.truncate(start);
+            break;
+        }
+    }
+    let status = str::from_utf8(&headers)
+        .ok()
+        .and_then(|head| head.split_whitespace().nth(1)?.parse().ok())
+        .ok_or(io::ErrorKind::InvalidData)?;
+    let header = |name: &str| {
+        parse_headers(&headers)
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    };
+    let content_length = match header("Content-Length") {
+        Some(value) => Some(value.parse().map_err(|_| io::ErrorKind::InvalidData)?),
+        None => None,
+    };
+    let chunked = header("Transfer-Encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked"));
+    let body = match (content_length, chunked) {
+        _ if head_request || matches!(status, 100..=199 | 204 | 304) => Body::Length(0),
+        (_, true) => Body::Chunked {
+            remaining: 0,
+            done: false,
+        },
+        (Some(content_length), false) => Body::Length(content_length),
+        (None, false) => Body::UntilClose,
+    };
+    Ok(Response {
+        status,
+        headers,
+        content_length,
+        body,
+    })
+}
+
+/// Splits `http://host[:port][/path]` into its host, port and path.
+fn parse_url(url: &str) -> Option<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (authority, 80),
+    };
+    match path.starts_with('?') {
+        true => Some((host.to_string(), port, format!("/{path}"))),
+        false => Some((host.to_string(), port, path.to_string())),
+    }
+}
+
+fn method_name(method: c_int) -> io::Result<&'static str> {
+    Ok(match method as c_uint {
+        SCE_HTTP_METHOD_GET => "GET",
+        SCE_HTTP_METHOD_POST => "POST",
+        SCE_HTTP_METHOD_HEAD => "HEAD",
+        SCE_HTTP_METHOD_OPTIONS => "OPTIONS",
+        SCE_HTTP_METHOD_PUT => "PUT",
+        SCE_HTTP_METHOD_DELETE => "DELETE",
+        SCE_HTTP_METHOD_TRACE => "TRACE",
+        SCE_HTTP_METHOD_CONNECT => "CONNECT",
+        _ => return Err(io::ErrorKind::InvalidInput.into()),
+    })
+}
+
+fn objects() -> MutexGuard<'static, BTreeMap<c_int, Object>> {
+    OBJECTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn insert(object: Object) -> c_int {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    objects().insert(id, object);
+    id
+}
+
+fn delete(id: c_int) -> c_int {
+    match objects().remove(&id) {
+        Some(_) => 0,
+        None => SCE_HTTP_ERROR_INVALID_ID as c_int,
+    }
+}
+
+fn request(id: c_int) -> Result<Arc<MockRequest>, c_int> {
+    match objects().get(&id) {
+        Some(Object::Request(request)) => Ok(Arc::clone(request)),
+        _ => Err(SCE_HTTP_ERROR_INVALID_ID as c_int),
+    }
+}
+
+fn with_settings(id: c_int, f: impl FnOnce(&mut Settings) -> c_int) -> c_int {
+    let request = match objects().get_mut(&id) {
+        Some(Object::Template(settings) | Object::Connection(settings, ..)) => return f(settings),
+        Some(Object::Request(request)) => Arc::clone(request),
+        None => return SCE_HTTP_ERROR_INVALID_ID as c_int,
+    };
+    let res = f(&mut request.state().settings);
+    res
+}
+
+fn with_response(id: c_int, f: impl FnOnce(&Response) -> c_int) -> c_int {
+    let request = match request(id) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let state = request.state();
+    match &state.response {
+        Some(response) => f(response),
+        None => SCE_HTTP_ERROR_BEFORE_SEND as c_int,
+    }
+}
+
+fn timeout(usec: c_uint) -> Option<Duration> {
+    (usec != 0).then(|| Duration::from_micros(usec.into()))
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+pub(crate) unsafe fn sceHttpInit(_poolSize: c_uint) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpTerm() -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceSslInit(_poolSize: c_uint) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceSslTerm() -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpCreateTemplate(
+    userAgent: *const c_char,
+    _httpVer: c_int,
+    _autoProxyConf: c_int,
+) -> c_int {
+    let Some(user_agent) = (unsafe { str_arg(userAgent) }) else {
+        return SCE_HTTP_ERROR_INVALID_ID as c_int;
+    };
+    insert(Object::Template(Settings {
+        headers: Vec::from([("User-Agent".to_string(), user_agent.to_string())]),
+        auto_redirect: true,
+        auth_enabled: true,
+        cookie_enabled: true,
+        connect_timeout: None,
+        send_timeout: None,
+        recv_timeout: None,
+        redirect_callback: (None, 0),
+    }))
+}
+
+/// Creates a connection, each request of which connects to the server itself.
+fn create_connection(tmplId: c_int, host: &str, port: u16) -> c_int {
+    let settings = match objects().get(&tmplId) {
+        Some(Object::Template(settings)) => settings.clone(),
+        _ => return SCE_HTTP_ERROR_INVALID_ID as c_int,
+    };
+    insert(Object::Connection(settings, host.to_string(), port))
+}
+
+pub(crate) unsafe fn sceHttpCreateConnection(
+    tmplId: c_int,
+    serverName: *const c_char,
+    scheme: *const c_char,
+    port: c_ushort,
+    _enableKeepalive: c_int,
+) -> c_int {
+    match unsafe { (str_arg(serverName), str_arg(scheme)) } {
+        (Some(host), Some("http")) => create_connection(tmplId, host, port),
+        (Some(_), _) => SCE_HTTP_ERROR_UNKNOWN_SCHEME as c_int,
+        (None, _) => SCE_HTTP_ERROR_INVALID_URL as c_int,
+    }
+}
+
+pub(crate) unsafe fn sceHttpCreateConnectionWithURL(
+    tmplId: c_int,
+    url: *const c_char,
+    _enableKeepalive: c_int,
+) -> c_int {
+    match unsafe { str_arg(url) } {
+        Some(url) if url.starts_with("https://") => SCE_HTTP_ERROR_UNKNOWN_SCHEME as c_int,
+        Some(url) => match parse_url(url) {
+            Some((host, port, _)) => create_connection(tmplId, &host, port),
+            None => SCE_HTTP_ERROR_INVALID_URL as c_int,
+        },
+        None => SCE_HTTP_ERROR_INVALID_URL as c_int,
+    }
+}
+
+fn create_request(connId: c_int, method: c_int, target: &str, contentLength: c_ulonglong) -> c_int {
+    if method_name(method).is_err() {
+        return SCE_HTTP_ERROR_UNKNOWN_METHOD as c_int;
+    }
+    let (mut settings, host, port) = match objects().get(&connId) {
+        Some(Object::Connection(settings, host, port)) => (settings.clone(), host.clone(), *port),
+        _ => return SCE_HTTP_ERROR_INVALID_ID as c_int,
+    };
+    // Requests through a proxy carry an absolute url as their path
+    if !target.starts_with('/') && !target.starts_with("http://") {
+        return SCE_HTTP_ERROR_INVALID_URL as c_int;
+    }
+    if contentLength != 0 || matches!(method as c_uint, SCE_HTTP_METHOD_POST | SCE_HTTP_METHOD_PUT)
+    {
+        settings
+            .headers
+            .push(("Content-Length".to_string(), contentLength.to_string()));
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let request = MockRequest {
+        state: Mutex::new(RequestState {
+            id,
+            settings,
+            method,
+            host,
+            port,
+            target: target.to_string(),
+            body: Vec::new(),
+            stream: None,
+            response: None,
+        }),
+        socket: Mutex::new(None),
+        aborted: AtomicBool::new(false),
+    };
+    objects().insert(id, Object::Request(Arc::new(request)));
+    id
+}
+
+pub(crate) unsafe fn sceHttpCreateRequest(
+    connId: c_int,
+    method: c_int,
+    path: *const c_char,
+    contentLength: c_ulonglong,
+) -> c_int {
+    match unsafe { str_arg(path) } {
+        Some(path) => create_request(connId, method, path, contentLength),
+        None => SCE_HTTP_ERROR_INVALID_URL as c_int,
+    }
+}
+
+pub(crate) unsafe fn sceHttpCreateRequestWithURL(
+    connId: c_int,
+    method: c_int,
+    url: *const c_char,
+    contentLength: c_ulonglong,
+) -> c_int {
+    match unsafe { str_arg(url) }.and_then(parse_url) {
+        Some((_, _, path)) => create_request(connId, method, &path, contentLength),
+        None => SCE_HTTP_ERROR_INVALID_URL as c_int,
+    }
+}
+
+pub(crate) unsafe fn sceHttpDeleteTemplate(tmplId: c_int) -> c_int {
+    delete(tmplId)
+}
+
+pub(crate) unsafe fn sceHttpDeleteConnection(connId: c_int) -> c_int {
+    delete(connId)
+}
+
+pub(crate) unsafe fn sceHttpDeleteRequest(reqId: c_int) -> c_int {
+    delete(reqId)
+}
+
+pub(crate) unsafe fn sceHttpSendRequest(
+    reqId: c_int,
+    postData: *const c_void,
+    size: c_uint,
+) -> c_int {
+    let request = match request(reqId) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let data = match size {
+        0 => &[][..],
+        size => unsafe { slice::from_raw_parts(postData.cast::<u8>(), size as usize) },
+    };
+    let mut state = request.state();
+    if state.response.is_some() {
+        return SCE_HTTP_ERROR_AFTER_SEND as c_int;
+    }
+    let res = (|| {
+        if state.stream.is_none() {
+            state.connect(&request)?;
+        }
+        state.write(data)?;
+        state.body.extend_from_slice(data);
+        if state.body_complete(data) {
+            state.read_response(&request)?;
+        }
+        Ok(())
+    })();
+    match res {
+        Ok(()) => 0,
+        Err(e) => request.error_code(e),
+    }
+}
+
+pub(crate) unsafe fn sceHttpAbortRequest(reqId: c_int) -> c_int {
+    let request = match request(reqId) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    request.aborted.store(true, Ordering::Relaxed);
+    if let Some(socket) = &*request.socket() {
+        let _ = socket.shutdown(Shutdown::Both);
+    }
+    0
+}
+
+pub(crate) unsafe fn sceHttpGetStatusCode(reqId: c_int, statusCode: *mut c_int) -> c_int {
+    with_response(reqId, |response| {
+        unsafe { *statusCode = response.status };
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpGetResponseContentLength(
+    reqId: c_int,
+    contentLength: *mut c_ulonglong,
+) -> c_int {
+    with_response(reqId, |response| match response.content_length {
+        Some(content_length) => {
+            unsafe { *contentLength = content_length };
+            0
+        }
+        None => SCE_HTTP_ERROR_NO_CONTENT_LENGTH as c_int,
+    })
+}
+
+pub(crate) unsafe fn sceHttpGetAllResponseHeaders(
+    reqId: c_int,
+    header: *mut *mut c_char,
+    headerSize: *mut c_uint,
+) -> c_int {
+    // Headers stay in place until the request is deleted
+    with_response(reqId, |response| {
+        unsafe {
+            *header = response.headers.as_ptr().cast_mut().cast();
+            *headerSize = response.headers.len() as c_uint;
+        }
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpReadData(reqId: c_int, data: *mut c_void, size: c_uint) -> c_int {
+    let request = match request(reqId) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let mut state = request.state();
+    if state.response.is_none() {
+        return SCE_HTTP_ERROR_BEFORE_SEND as c_int;
+    }
+    let buf = match size {
+        0 => &mut [][..],
+        size => unsafe { slice::from_raw_parts_mut(data.cast::<u8>(), size as usize) },
+    };
+    match state.read_body(buf) {
+        Ok(n) => n as c_int,
+        Err(e) => request.error_code(e),
+    }
+}
+
+pub(crate) unsafe fn sceHttpSetRequestContentLength(
+    id: c_int,
+    contentLength: c_ulonglong,
+) -> c_int {
+    let request = match request(id) {
+        Ok(request) => request,
+        Err(e) => return e,
+    };
+    let settings = &mut request.state().settings;
+    settings.remove_header("Content-Length");
+    settings
+        .headers
+        .push(("Content-Length".to_string(), contentLength.to_string()));
+    0
+}
+
+pub(crate) unsafe fn sceHttpAddRequestHeader(
+    id: c_int,
+    name: *const c_char,
+    value: *const c_char,
+    mode: SceHttpAddHeaderMode,
+) -> c_int {
+    let (Some(name), Some(value)) = (unsafe { str_arg(name) }, unsafe { str_arg(value) }) else {
+        return SCE_HTTP_ERROR_INVALID_VALUE as c_int;
+    };
+    with_settings(id, |settings| {
+        if mode == SCE_HTTP_HEADER_OVERWRITE {
+            settings.remove_header(name);
+        }
+        settings.headers.push((name.to_string(), value.to_string()));
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpRemoveRequestHeader(id: c_int, name: *const c_char) -> c_int {
+    let name = unsafe { str_arg(name) }.unwrap_or_default();
+    with_settings(id, |settings| match settings.remove_header(name) {
+        true => 0,
+        false => SCE_HTTP_ERROR_NOT_FOUND as c_int,
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetAutoRedirect(id: c_int, enable: c_int) -> c_int {
+    with_settings(id, |settings| {
+        settings.auto_redirect = enable != 0;
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpGetAutoRedirect(id: c_int, enable: *mut c_int) -> c_int {
+    with_settings(id, |settings| {
+        unsafe { *enable = settings.auto_redirect as c_int };
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetAuthEnabled(id: c_int, enable: c_int) -> c_int {
+    with_settings(id, |settings| {
+        settings.auth_enabled = enable != 0;
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpGetAuthEnabled(id: c_int, enable: *mut c_int) -> c_int {
+    with_settings(id, |settings| {
+        unsafe { *enable = settings.auth_enabled as c_int };
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetCookieEnabled(id: c_int, enable: c_int) -> c_int {
+    with_settings(id, |settings| {
+        settings.cookie_enabled = enable != 0;
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpGetCookieEnabled(id: c_int, enable: *mut c_int) -> c_int {
+    with_settings(id, |settings| {
+        unsafe { *enable = settings.cookie_enabled as c_int };
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetConnectTimeOut(id: c_int, usec: c_uint) -> c_int {
+    with_settings(id, |settings| {
+        settings.connect_timeout = timeout(usec);
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetSendTimeOut(id: c_int, usec: c_uint) -> c_int {
+    with_settings(id, |settings| {
+        settings.send_timeout = timeout(usec);
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetRecvTimeOut(id: c_int, usec: c_uint) -> c_int {
+    with_settings(id, |settings| {
+        settings.recv_timeout = timeout(usec);
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetResolveTimeOut(id: c_int, _usec: c_uint) -> c_int {
+    with_settings(id, |_| 0)
+}
+
+pub(crate) unsafe fn sceHttpSetResolveRetry(id: c_int, _retry: c_int) -> c_int {
+    with_settings(id, |_| 0)
+}
+
+pub(crate) unsafe fn sceHttpSetRedirectCallback(
+    id: c_int,
+    cbfunc: SceHttpRedirectCallback,
+    userArg: *mut c_void,
+) -> c_int {
+    with_settings(id, |settings| {
+        settings.redirect_callback = (cbfunc, userArg as usize);
+        0
+    })
+}
+
+pub(crate) unsafe fn sceHttpSetAuthInfoCallback(
+    id: c_int,
+    _cbfunc: SceHttpAuthInfoCallback,
+    _userArg: *mut c_void,
+) -> c_int {
+    with_settings(id, |_| 0)
+}
+
+pub(crate) unsafe fn sceHttpSetCookieRecvCallback(
+    id: c_int,
+    _cbfunc: SceHttpCookieRecvCallback,
+    _userArg: *mut c_void,
+) -> c_int {
+    with_settings(id, |_| 0)
+}
+
+pub(crate) unsafe fn sceHttpAddCookie(
+    _url: *const c_char,
+    _cookie: *const c_char,
+    _cookieLength: c_uint,
+) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpsEnableOption(_sslFlags: c_uint) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpsDisableOption(_sslFlags: c_uint) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpsLoadCert(
+    _caCertNum: c_int,
+    _caList: *mut *const SceHttpsData,
+    _cert: *const SceHttpsData,
+    _privKey: *const SceHttpsData,
+) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpsUnloadCert() -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceHttpsSetSslCallback(
+    id: c_int,
+    _cbfunc: SceHttpsCallback,
+    _userArg: *mut c_void,
+) -> c_int {
+    with_settings(id, |_| 0)
+}
+
+pub(crate) unsafe fn sceHttpsGetSslError(
+    id: c_int,
+    errNum: *mut c_int,
+    detail: *mut c_uint,
+) -> c_int {
+    match request(id) {
+        Ok(_) => {
+            unsafe {
+                *errNum = 0;
+                *detail = 0;
+            }
+            0
+        }
+        Err(e) => e,
+    }
+}
+
+// Certificates are only passed to the SSL callback, which is never called
+
+pub(crate) unsafe fn sceSslGetSubjectName(_sslCert: *mut SceSslCert) -> *mut SceSslCertName {
+    ptr::null_mut()
+}
+
+pub(crate) unsafe fn sceSslGetIssuerName(_sslCert: *mut SceSslCert) -> *mut SceSslCertName {
+    ptr::null_mut()
+}
+
+pub(crate) unsafe fn sceSslGetNotBefore(
+    _sslCert: *mut SceSslCert,
+    _begin: *mut SceRtcTick,
+) -> c_int {
+    SCE_SSL_ERROR_NOT_FOUND as c_int
+}
+
+pub(crate) unsafe fn sceSslGetNotAfter(
+    _sslCert: *mut SceSslCert,
+    _limit: *mut SceRtcTick,
+) -> c_int {
+    SCE_SSL_ERROR_NOT_FOUND as c_int
+}
+
+pub(crate) unsafe fn sceSslGetSerialNumber(
+    _sslCert: *mut SceSslCert,
+    _sboData: *mut *const c_char,
+    _sboLen: *mut c_uint,
+) -> c_int {
+    SCE_SSL_ERROR_NOT_FOUND as c_int
+}
+
+pub(crate) unsafe fn sceSslGetNameEntryCount(_certName: *mut SceSslCertName) -> c_int {
+    0
+}
+
+pub(crate) unsafe fn sceSslGetNameEntryInfo(
+    _certName: *mut SceSslCertName,
+    _entryNum: c_int,
+    _oidname: *mut c_char,
+    _maxOidnameLen: c_uint,
+    _value: *mut c_char,
+    _maxValueLen: c_uint,
+    _valueLen: *mut c_uint,
+) -> c_int {
+    SCE_SSL_ERROR_NOT_FOUND as c_int
+}
+
+pub(crate) unsafe fn sceSslFreeSslCertName(_certName: *mut SceSslCertName) -> c_int {
+    0
+}
@@ -0,0 +1,113 @@
+use core::ffi::CStr;
+
+#[cfg(any(feature = "alloc", test))]
+use super::ClientOptions;
+use super::{AutoProxyConf, HttpVersion, Template};
+use crate::error::SceResult;
+
+/// Builder of a [`Template`] with its proxy and connection settings.
+///
+/// The system HTTP library has no API to set a proxy server explicitly, so
+/// connections created with [`TemplateBuilder::with_proxy`] connect to the
+/// proxy server and send requests in absolute form. Only plain `http` URLs
+/// are supported, others failing with `SCE_HTTPS_ERROR_PROXY`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct TemplateBuilder<'a> {
+    user_agent: &'a CStr,
+    http_ver: HttpVersion,
+    system_proxy: AutoProxyConf,
+    #[cfg(any(feature = "alloc", test))]
+    proxy: Option<(&'a CStr, u16)>,
+    #[cfg(any(feature = "alloc", test))]
+    max_connections: Option<usize>,
+}
+
+impl Default for TemplateBuilder<'_> {
+    fn default() -> Self {
+        TemplateBuilder::new()
+    }
+}
+
+impl<'a> TemplateBuilder<'a> {
+    pub fn new() -> Self {
+        TemplateBuilder {
+            user_agent: c"vitasdk-rust",
+            http_ver: HttpVersion::V1_1,
+            system_proxy: AutoProxyConf::Enable,
+            #[cfg(any(feature = "alloc", test))]
+            proxy: None,
+            #[cfg(any(feature = "alloc", test))]
+            max_connections: None,
+        }
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &'a CStr) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn with_http_version(mut self, http_ver: HttpVersion) -> Self {
+        self.http_ver = http_ver;
+        self
+    }
+
+    /// Sets whether the proxy from the system network settings is used,
+    /// `true` by default.
+    pub fn with_system_proxy(mut self, enable: bool) -> Self {
+        self.system_proxy = match enable {
+            true => AutoProxyConf::Enable,
+            false => AutoProxyConf::Disable,
+        };
+        self
+    }
+
+    /// Sets a HTTP proxy server used instead of the system proxy, by every
+    /// connection created from the template.
+    #[cfg(any(feature = "alloc", test))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn with_proxy(mut self, host: &'a CStr, port: u16) -> Self {
+        self.proxy = Some((host, port));
+        self.system_proxy = AutoProxyConf::Disable;
+        self
+    }
+
+    /// Disables both the system proxy and the proxy set with
+    /// [`TemplateBuilder::with_proxy`].
+    pub fn without_proxy(mut self) -> Self {
+        #[cfg(any(feature = "alloc", test))]
+        {
+            self.proxy = None;
+        }
+        self.system_proxy = AutoProxyConf::Disable;
+        self
+    }
+
+    /// Limits number of connections kept alive by [`Client`](super::Client)
+    /// using the template, values below one are treated as one.
+    ///
+    /// Once the limit is reached, the least recently used connection is
+    /// closed to make room for the one of a new origin. Connections still
+    /// read by a response are evicted last, and closed once it's dropped.
+    #[cfg(any(feature = "alloc", test))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections.max(1));
+        self
+    }
+
+    pub fn build(self) -> SceResult<Template> {
+        #[allow(unused_mut)]
+        let mut template = Template::new(self.user_agent, self.http_ver, self.system_proxy)?;
+        #[cfg(any(feature = "alloc", test))]
+        {
+            template.client_options = ClientOptions {
+                proxy: self
+                    .proxy
+                    .map(|(host, port)| (alloc::ffi::CString::from(host), port)),
+                max_connections: self.max_connections,
+            };
+        }
+        Ok(template)
+    }
+}
@@ -1,7 +1,19 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
-#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(clippy::std_instead_of_alloc, clippy::std_instead_of_core)]
 
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+#[cfg(any(feature = "audio", feature = "mixer", test))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "audio", feature = "mixer"))))]
+pub mod audio;
+#[cfg(feature = "callback")]
+#[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+pub mod callback;
+#[cfg(any(feature = "ctrl", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ctrl")))]
+pub mod ctrl;
 #[cfg(feature = "display")]
 #[cfg_attr(docsrs, doc(cfg(feature = "display")))]
 pub mod display;
@@ -9,18 +21,40 @@ pub mod display;
 #[cfg_attr(docsrs, doc(cfg(feature = "dmac")))]
 pub mod dmac;
 pub mod error;
-#[cfg(feature = "http")]
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod executor;
+#[cfg(any(feature = "http", test))]
 #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
 pub mod http;
+#[cfg(any(feature = "audio", feature = "motion", feature = "touch", test))]
+mod math;
+#[cfg(any(feature = "motion", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "motion")))]
+pub mod motion;
 #[cfg(feature = "net")]
 #[cfg_attr(docsrs, doc(cfg(feature = "net")))]
 pub mod net;
+#[cfg(any(feature = "http", feature = "websocket", test))]
+mod proto;
+#[cfg(feature = "sync")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+pub mod sync;
 #[cfg(feature = "sysmem")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sysmem")))]
 pub mod sysmem;
 #[cfg(feature = "sysmodule")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sysmodule")))]
 pub mod sysmodule;
+#[cfg(feature = "thread")]
+#[cfg_attr(docsrs, doc(cfg(feature = "thread")))]
+pub mod thread;
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub mod time;
+#[cfg(any(feature = "touch", test))]
+#[cfg_attr(docsrs, doc(cfg(feature = "touch")))]
+pub mod touch;
 mod types;
 
 pub use error::{SceError, SceResult};
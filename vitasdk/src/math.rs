@@ -0,0 +1,11 @@
+/// Square root for `no_std`, precise to a few ulps.
+pub(crate) fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let mut x = f32::from_bits((value.to_bits() >> 1) + 0x1fbd_1df5);
+    for _ in 0..3 {
+        x = 0.5 * (x + value / x);
+    }
+    x
+}
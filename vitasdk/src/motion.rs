@@ -0,0 +1,75 @@
+mod geometry;
+#[cfg(feature = "motion")]
+mod sensor;
+
+pub use geometry::{Quaternion, Vector3};
+#[cfg(feature = "motion")]
+pub use sensor::Motion;
+
+/// State of the device computed by the motion library.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct MotionState {
+    /// Sampling time in microseconds.
+    pub timestamp: u32,
+    /// Acceleration in G units, including gravity.
+    pub acceleration: Vector3,
+    /// Angular velocity in radians per second.
+    pub angular_velocity: Vector3,
+    /// Orientation relative to the reference one.
+    pub orientation: Quaternion,
+    /// Axis pointing up, each component being -1, 0 or 1.
+    pub basic_orientation: Vector3,
+}
+
+impl MotionState {
+    /// Returns direction of the gravity relative to the device, whose X and
+    /// Y components are the sine of its tilt around the Y and X axes.
+    ///
+    /// It's derived from the acceleration, so it's only accurate while the
+    /// device isn't otherwise accelerating.
+    pub fn gravity(&self) -> Vector3 {
+        self.acceleration.normalize()
+    }
+}
+
+/// Raw sensor values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct SensorState {
+    /// Sampling time in microseconds.
+    pub timestamp: u32,
+    pub counter: u32,
+    /// Acceleration in G units, including gravity.
+    pub accelerometer: Vector3,
+    /// Angular velocity in radians per second.
+    pub gyro: Vector3,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(acceleration: Vector3) -> MotionState {
+        MotionState {
+            timestamp: 0,
+            acceleration,
+            angular_velocity: Vector3::default(),
+            orientation: Quaternion::IDENTITY,
+            basic_orientation: Vector3::default(),
+        }
+    }
+
+    #[test]
+    fn gravity_is_normalized_acceleration() {
+        let gravity = state(Vector3::new(0.0, 0.0, -2.0)).gravity();
+        assert_eq!(gravity, Vector3::new(0.0, 0.0, -1.0));
+        let gravity = state(Vector3::new(0.3, -0.4, 0.0)).gravity();
+        assert!((gravity.x - 0.6).abs() < 1e-5 && (gravity.y + 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn gravity_of_free_fall() {
+        assert_eq!(state(Vector3::default()).gravity(), Vector3::default());
+    }
+}
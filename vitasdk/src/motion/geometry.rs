@@ -0,0 +1,216 @@
+use core::ops;
+
+use vitasdk_sys::{SceFQuaternion, SceFVector3};
+
+use crate::math::sqrt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vector3 { x, y, z }
+    }
+
+    pub fn dot(self, other: Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vector3) -> Vector3 {
+        Vector3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn length(self) -> f32 {
+        sqrt(self.dot(self))
+    }
+
+    /// Returns the zero vector unchanged.
+    pub fn normalize(self) -> Vector3 {
+        match self.length() {
+            length if length > 0.0 => self * (1.0 / length),
+            _ => self,
+        }
+    }
+}
+
+impl From<SceFVector3> for Vector3 {
+    fn from(v: SceFVector3) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl ops::Add for Vector3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl ops::Sub for Vector3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl ops::Mul<f32> for Vector3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Vector3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+/// Rotation as a unit quaternion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::IDENTITY
+    }
+}
+
+impl Quaternion {
+    pub const IDENTITY: Self = Quaternion::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Quaternion { x, y, z, w }
+    }
+
+    /// Returns the inverse rotation.
+    pub fn conjugate(self) -> Quaternion {
+        Quaternion::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// Rotates `v` from device space into reference space.
+    pub fn rotate(self, v: Vector3) -> Vector3 {
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+
+    /// Returns the rotation from `self` to `other`.
+    pub fn delta(self, other: Quaternion) -> Quaternion {
+        self.conjugate() * other
+    }
+}
+
+impl From<SceFQuaternion> for Quaternion {
+    fn from(q: SceFQuaternion) -> Self {
+        Quaternion::new(q.x, q.y, q.z, q.w)
+    }
+}
+
+impl ops::Mul for Quaternion {
+    type Output = Self;
+
+    /// Composes rotations, applying `rhs` first.
+    fn mul(self, rhs: Self) -> Self {
+        Quaternion {
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_1_SQRT_2;
+
+    use super::*;
+
+    /// Quarter turn around the Z axis, rotating X onto Y.
+    const QUARTER_Z: Quaternion = Quaternion::new(0.0, 0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+
+    fn assert_near(a: Vector3, b: Vector3) {
+        assert!((a - b).length() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn vector_products() {
+        let (x, y, z) = (
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(x), z * -1.0);
+        assert_eq!(x.dot(y), 0.0);
+        assert_eq!(
+            Vector3::new(1.0, 2.0, 3.0).dot(Vector3::new(4.0, 5.0, 6.0)),
+            32.0
+        );
+    }
+
+    #[test]
+    fn vector_length() {
+        let v = Vector3::new(3.0, 0.0, 4.0);
+        assert!((v.length() - 5.0).abs() < 1e-5);
+        assert_near(v.normalize(), Vector3::new(0.6, 0.0, 0.8));
+        assert_eq!(Vector3::default().normalize(), Vector3::default());
+    }
+
+    #[test]
+    fn vector_arithmetic() {
+        let (a, b) = (Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.5, -1.0, 2.0));
+        assert_eq!(a + b, Vector3::new(1.5, 1.0, 5.0));
+        assert_eq!(a - b, Vector3::new(0.5, 3.0, 1.0));
+        assert_eq!(a * 2.0, Vector3::new(2.0, 4.0, 6.0));
+    }
+
+    #[test]
+    fn rotation() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(Quaternion::IDENTITY.rotate(x), x);
+        assert_near(QUARTER_Z.rotate(x), Vector3::new(0.0, 1.0, 0.0));
+        assert_near(
+            QUARTER_Z.conjugate().rotate(x),
+            Vector3::new(0.0, -1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn composition() {
+        let half = QUARTER_Z * QUARTER_Z;
+        assert_near(
+            half.rotate(Vector3::new(1.0, 0.0, 0.0)),
+            Vector3::new(-1.0, 0.0, 0.0),
+        );
+
+        // Quarter turn around X, applied before the one around Z
+        let quarter_x = Quaternion::new(FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2);
+        let v = Vector3::new(0.0, 1.0, 0.0);
+        assert_near(
+            (QUARTER_Z * quarter_x).rotate(v),
+            QUARTER_Z.rotate(quarter_x.rotate(v)),
+        );
+    }
+
+    #[test]
+    fn delta() {
+        let quarter_x = Quaternion::new(FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2);
+        let delta = QUARTER_Z.delta(quarter_x);
+        let v = Vector3::new(0.3, -0.2, 0.9);
+        assert_near((QUARTER_Z * delta).rotate(v), quarter_x.rotate(v));
+        let identity = QUARTER_Z.delta(QUARTER_Z);
+        assert_near(identity.rotate(v), v);
+    }
+}
@@ -0,0 +1,150 @@
+use core::{
+    ffi::c_int,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use vitasdk_sys::{
+    sceMotionGetAngleThreshold, sceMotionGetDeadband, sceMotionGetSensorState, sceMotionGetState,
+    sceMotionMagnetometerOff, sceMotionMagnetometerOn, sceMotionReset, sceMotionRotateYaw,
+    sceMotionSetAngleThreshold, sceMotionSetDeadband, sceMotionSetGyroBiasCorrection,
+    sceMotionSetTiltCorrection, sceMotionStartSampling, sceMotionStopSampling,
+    SceMotionSensorState, SceMotionState,
+};
+
+use super::{MotionState, SensorState};
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceResult};
+
+static USERS: AtomicU32 = AtomicU32::new(0);
+
+/// Accelerometer and gyroscope sampling.
+///
+/// Sampling starts when the first instance is created, and stops once all of
+/// them are dropped.
+#[derive(Debug)]
+pub struct Motion {
+    _private: (),
+}
+
+impl Motion {
+    #[doc(alias = "sceMotionStartSampling")]
+    pub fn start() -> SceResult<Self> {
+        if USERS.fetch_add(1, Ordering::AcqRel) == 0 {
+            if let Err(e) = sce_result_unit_from_code(unsafe { sceMotionStartSampling() }) {
+                USERS.fetch_sub(1, Ordering::AcqRel);
+                return Err(e);
+            }
+        }
+        Ok(Motion { _private: () })
+    }
+
+    /// Returns the latest state computed out of the sensors.
+    #[doc(alias = "sceMotionGetState")]
+    pub fn state(&self) -> SceResult<MotionState> {
+        let mut state = MaybeUninit::<SceMotionState>::zeroed();
+        sce_result_unit_from_code(unsafe { sceMotionGetState(state.as_mut_ptr()) })?;
+        let state = unsafe { state.assume_init() };
+        Ok(MotionState {
+            timestamp: state.timestamp,
+            acceleration: state.acceleration.into(),
+            angular_velocity: state.angularVelocity.into(),
+            orientation: state.deviceQuat.into(),
+            basic_orientation: state.basicOrientation.into(),
+        })
+    }
+
+    /// Returns the latest raw sensor values.
+    #[doc(alias = "sceMotionGetSensorState")]
+    pub fn sensor_state(&self) -> SceResult<SensorState> {
+        let mut state = MaybeUninit::<SceMotionSensorState>::zeroed();
+        sce_result_usize_from_code(unsafe { sceMotionGetSensorState(state.as_mut_ptr(), 1) })?;
+        let state = unsafe { state.assume_init() };
+        Ok(SensorState {
+            timestamp: state.timestamp,
+            counter: state.counter,
+            accelerometer: state.accelerometer.into(),
+            gyro: state.gyro.into(),
+        })
+    }
+
+    /// Sets the current orientation as the reference one and clears the
+    /// accumulated drift.
+    #[doc(alias = "sceMotionReset")]
+    pub fn reset(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionReset() })
+    }
+
+    /// Rotates the reference orientation around the yaw axis.
+    #[doc(alias = "sceMotionRotateYaw")]
+    pub fn rotate_yaw(&self, radians: f32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionRotateYaw(radians) })
+    }
+
+    /// Sets whether small angular velocities are ignored, which prevents
+    /// orientation from drifting while the device is still.
+    #[doc(alias = "sceMotionSetDeadband")]
+    pub fn set_deadband(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionSetDeadband(enable as c_int) })
+    }
+
+    #[doc(alias = "sceMotionGetDeadband")]
+    pub fn deadband(&self) -> SceResult<bool> {
+        sce_result_usize_from_code(unsafe { sceMotionGetDeadband() }).map(|deadband| deadband != 0)
+    }
+
+    /// Sets the angle in radians the orientation has to change by before
+    /// the basic orientation is updated.
+    #[doc(alias = "sceMotionSetAngleThreshold")]
+    pub fn set_angle_threshold(&self, radians: f32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionSetAngleThreshold(radians) })
+    }
+
+    #[doc(alias = "sceMotionGetAngleThreshold")]
+    pub fn angle_threshold(&self) -> f32 {
+        unsafe { sceMotionGetAngleThreshold() }
+    }
+
+    /// Sets whether the accelerometer is used to correct the tilt of the
+    /// orientation.
+    #[doc(alias = "sceMotionSetTiltCorrection")]
+    pub fn set_tilt_correction(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionSetTiltCorrection(enable as c_int) })
+    }
+
+    /// Sets whether gyroscope bias is corrected while the device is still.
+    #[doc(alias = "sceMotionSetGyroBiasCorrection")]
+    pub fn set_gyro_bias_correction(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceMotionSetGyroBiasCorrection(enable as c_int) })
+    }
+
+    /// Sets whether the magnetometer is used to correct the yaw of the
+    /// orientation.
+    #[doc(alias = "sceMotionMagnetometerOn")]
+    #[doc(alias = "sceMotionMagnetometerOff")]
+    pub fn set_magnetometer(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            match enable {
+                true => sceMotionMagnetometerOn(),
+                false => sceMotionMagnetometerOff(),
+            }
+        })
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn stop(self) -> SceResult<()> {
+        core::mem::ManuallyDrop::new(self).stop_()
+    }
+
+    fn stop_(&mut self) -> SceResult<()> {
+        match USERS.fetch_sub(1, Ordering::AcqRel) {
+            1 => sce_result_unit_from_code(unsafe { sceMotionStopSampling() }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for Motion {
+    fn drop(&mut self) {
+        let _ = self.stop_();
+    }
+}
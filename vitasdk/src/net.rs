@@ -1,56 +1,211 @@
-use std::ffi::CStr;
+use core::mem::MaybeUninit;
 
-use vitasdk_sys::{
-    sceHttpInit, sceHttpTerm, sceNetCtlInit, sceNetCtlTerm, sceNetInit, sceNetTerm, SceNetInitParam,
-};
+use vitasdk_sys::{sceNetInit, sceNetTerm, SceNetInitParam};
 
 use crate::{
     error::{sce_result_unit_from_code, SceResult},
-    memblock::{MemBlockMut, MemBlockOptions},
-    module::{Module, ModuleId},
+    sysmem::{MemBlockOptions, MemBlockUninitMut, MemPartition},
+    sysmodule::{Module, ModuleId},
 };
 
+#[cfg(feature = "async")]
+mod async_tcp;
+mod ctl;
+mod resolver;
+mod tcp;
+#[cfg(feature = "websocket")]
+#[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+pub mod websocket;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use async_tcp::AsyncTcpStream;
+#[cfg(feature = "alloc")]
+pub use ctl::InetCallback;
+pub use ctl::{NetCtl, NetCtlEvent, NetCtlInfo, NetCtlState, Ssid};
+pub use resolver::{resolve, resolve_addr, Hostname, Resolver};
+pub use tcp::{Shutdown, TcpStream};
+
 pub struct GlobalState {
+    // Fields are dropped in reverse initialization order
+    net_ctl: Option<NetCtl>,
+    _net: NetGuard,
+    _memory: NetMemory,
     _module: Module,
-    _memory: MemBlockMut,
 }
 
 impl GlobalState {
     pub fn new() -> SceResult<Self> {
-        const MEMORY_SIZE: usize = 0x400000;
-        Self::with_memory_size(MEMORY_SIZE)
+        GlobalStateOptions::new().init()
     }
 
     pub fn with_memory_size(size: usize) -> SceResult<Self> {
+        GlobalStateOptions::new().with_memory_size(size).init()
+    }
+
+    /// Returns `None` if initialized with [`GlobalStateOptions::with_net_ctl`]
+    /// set to `false`.
+    pub fn net_ctl(&self) -> Option<&NetCtl> {
+        self.net_ctl.as_ref()
+    }
+}
+
+#[derive(Debug)]
+pub struct GlobalStateOptions {
+    memory: MemoryOptions,
+    flags: i32,
+    net_ctl: bool,
+}
+
+#[derive(Debug)]
+enum MemoryOptions {
+    Alloc {
+        size: usize,
+        mem_partition: MemPartition,
+    },
+    Provided(NetMemory),
+}
+
+#[derive(Debug)]
+enum NetMemory {
+    MemBlock(MemBlockUninitMut),
+    Static(&'static mut [MaybeUninit<u8>]),
+}
+
+impl NetMemory {
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        match self {
+            NetMemory::MemBlock(memblock) => memblock.as_mut_ptr(),
+            NetMemory::Static(slice) => slice.as_mut_ptr().cast(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            NetMemory::MemBlock(memblock) => memblock.len(),
+            NetMemory::Static(slice) => slice.len(),
+        }
+    }
+}
+
+impl Default for GlobalStateOptions {
+    fn default() -> Self {
+        GlobalStateOptions::new()
+    }
+}
+
+impl GlobalStateOptions {
+    pub const DEFAULT_MEMORY_SIZE: usize = 0x400000;
+
+    pub fn new() -> Self {
+        GlobalStateOptions {
+            memory: MemoryOptions::Alloc {
+                size: Self::DEFAULT_MEMORY_SIZE,
+                mem_partition: MemPartition::Main,
+            },
+            flags: 0,
+            net_ctl: true,
+        }
+    }
+
+    /// Sets size of the memory block allocated for the network stack.
+    pub fn with_memory_size(mut self, size: usize) -> Self {
+        self.memory = MemoryOptions::Alloc {
+            size,
+            mem_partition: self.mem_partition(),
+        };
+        self
+    }
+
+    /// Sets partition of the memory block allocated for the network stack.
+    pub fn with_memory_partition(mut self, mem_partition: MemPartition) -> Self {
+        self.memory = MemoryOptions::Alloc {
+            size: self.memory_size(),
+            mem_partition,
+        };
+        self
+    }
+
+    /// Uses caller-supplied memory block instead of allocating one.
+    pub fn with_memblock(mut self, memblock: MemBlockUninitMut) -> Self {
+        self.memory = MemoryOptions::Provided(NetMemory::MemBlock(memblock));
+        self
+    }
+
+    /// Uses caller-supplied static buffer instead of allocating a memory block.
+    pub fn with_static_memory(mut self, memory: &'static mut [MaybeUninit<u8>]) -> Self {
+        self.memory = MemoryOptions::Provided(NetMemory::Static(memory));
+        self
+    }
+
+    /// Sets `SceNetInitParam.flags`.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets whether to initialize the connection manager, `true` by default.
+    pub fn with_net_ctl(mut self, net_ctl: bool) -> Self {
+        self.net_ctl = net_ctl;
+        self
+    }
+
+    pub fn init(self) -> SceResult<GlobalState> {
         let module = Module::load(ModuleId::NET)?;
-        static MEMORY_NAME: &CStr = match CStr::from_bytes_with_nul(b"SceNetMemory\0") {
-            Ok(v) => v,
-            Err(_) => panic!(),
+        let mut memory = match self.memory {
+            MemoryOptions::Alloc {
+                size,
+                mem_partition,
+            } => NetMemory::MemBlock(
+                MemBlockOptions::from_size(size)
+                    .with_name(c"SceNetMemory")
+                    .with_memory_partition(mem_partition)
+                    .alloc_mut()?,
+            ),
+            MemoryOptions::Provided(memory) => memory,
         };
-        let memory = MemBlockOptions::from_size(size)
-            .with_name(MEMORY_NAME)
-            .alloc_mut()?;
         let mut param = SceNetInitParam {
             memory: memory.as_mut_ptr().cast(),
-            size: size as i32,
-            flags: 0,
+            size: memory.len() as i32,
+            flags: self.flags,
         };
         sce_result_unit_from_code(unsafe { sceNetInit(&mut param) })?;
-        sce_result_unit_from_code(unsafe { sceNetCtlInit() }).map_err(|e| {
-            // TODO: Use drop impl
-            let _ = unsafe { sceNetTerm() };
-            e
-        })?;
+        let net = NetGuard { _private: () };
+        let net_ctl = if self.net_ctl {
+            Some(NetCtl::init()?)
+        } else {
+            None
+        };
         Ok(GlobalState {
-            _module: module,
+            net_ctl,
+            _net: net,
             _memory: memory,
+            _module: module,
         })
     }
+
+    fn memory_size(&self) -> usize {
+        match &self.memory {
+            MemoryOptions::Alloc { size, .. } => *size,
+            MemoryOptions::Provided(memory) => memory.len(),
+        }
+    }
+
+    fn mem_partition(&self) -> MemPartition {
+        match &self.memory {
+            MemoryOptions::Alloc { mem_partition, .. } => *mem_partition,
+            MemoryOptions::Provided(_) => MemPartition::default(),
+        }
+    }
+}
+
+/// Terminates the network stack on drop.
+struct NetGuard {
+    _private: (),
 }
 
-impl Drop for GlobalState {
+impl Drop for NetGuard {
     fn drop(&mut self) {
-        unsafe { sceNetCtlTerm() };
         let _ = unsafe { sceNetTerm() };
     }
 }
@@ -0,0 +1,165 @@
+use core::{
+    future::poll_fn,
+    mem,
+    net::SocketAddrV4,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io;
+
+use vitasdk_sys::{
+    sceNetConnect, SceNetSockaddr, SceNetSockaddrIn, SCE_NET_ERROR_EAGAIN,
+    SCE_NET_ERROR_EINPROGRESS, SCE_NET_ERROR_ENOTCONN, SCE_NET_ERROR_EPIPE,
+};
+
+use super::{
+    resolve,
+    tcp::{sockaddr_from, TcpStream},
+};
+use crate::{
+    error::{sce_result_unit_from_code, SceError, SceResult},
+    executor::{try_with_reactor, with_reactor, Interest},
+};
+
+/// Non-blocking [`TcpStream`] driven by the [`executor`](crate::executor).
+///
+/// It must be used within [`Executor::block_on`](crate::executor::Executor::block_on).
+#[derive(Debug)]
+pub struct AsyncTcpStream {
+    inner: TcpStream,
+}
+
+impl AsyncTcpStream {
+    pub async fn connect(addr: SocketAddrV4) -> SceResult<Self> {
+        let stream = AsyncTcpStream::from_std(TcpStream::new(c"SceNetTcpStream")?)?;
+        let addr = sockaddr_from(addr);
+        match sce_result_unit_from_code(unsafe {
+            sceNetConnect(
+                stream.inner.as_raw_id(),
+                (&addr as *const SceNetSockaddrIn).cast::<SceNetSockaddr>(),
+                mem::size_of::<SceNetSockaddrIn>() as u32,
+            )
+        }) {
+            Ok(()) => return Ok(stream),
+            Err(e) if e == SceError::from_error_code(SCE_NET_ERROR_EINPROGRESS) => {}
+            Err(e) => return Err(e),
+        }
+        // Socket becomes writable once the connection completes or fails, the
+        // task could be polled before that though
+        poll_fn(|cx| {
+            if let Some(e) = stream.inner.take_error()? {
+                return Poll::Ready(Err(e));
+            }
+            match stream.inner.peer_addr() {
+                Ok(_) => Poll::Ready(Ok(())),
+                Err(e) if e == SceError::from_error_code(SCE_NET_ERROR_ENOTCONN) => {
+                    match with_reactor(|reactor| {
+                        reactor.register(stream.inner.as_raw_id(), Interest::Write, cx.waker())
+                    }) {
+                        Ok(()) => Poll::Pending,
+                        Err(e) => Poll::Ready(Err(e)),
+                    }
+                }
+                Err(e) => Poll::Ready(Err(e)),
+            }
+        })
+        .await?;
+        Ok(stream)
+    }
+
+    /// Resolves `host` and connects to its address.
+    ///
+    /// Host name resolution blocks the executor.
+    pub async fn connect_to(host: &str, port: u16) -> SceResult<Self> {
+        AsyncTcpStream::connect(SocketAddrV4::new(resolve(host)?, port)).await
+    }
+
+    /// Switches a connected stream into non-blocking mode.
+    pub fn from_std(stream: TcpStream) -> SceResult<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(AsyncTcpStream { inner: stream })
+    }
+
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.inner
+    }
+
+    pub async fn read(&self, buf: &mut [u8]) -> SceResult<usize> {
+        poll_fn(|cx| self.poll_io(cx, Interest::Read, || self.inner.read(buf))).await
+    }
+
+    pub async fn write(&self, buf: &[u8]) -> SceResult<usize> {
+        poll_fn(|cx| self.poll_io(cx, Interest::Write, || self.inner.write(buf))).await
+    }
+
+    /// Same as [`TcpStream::write_all`].
+    pub async fn write_all(&self, mut buf: &[u8]) -> SceResult<()> {
+        while !buf.is_empty() {
+            match self.write(buf).await? {
+                0 => return Err(SceError::from_error_code(SCE_NET_ERROR_EPIPE)),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    fn poll_io<T>(
+        &self,
+        cx: &mut Context<'_>,
+        interest: Interest,
+        mut f: impl FnMut() -> SceResult<T>,
+    ) -> Poll<SceResult<T>> {
+        match f() {
+            Err(e) if e == SceError::from_error_code(SCE_NET_ERROR_EAGAIN) => {
+                match with_reactor(|reactor| {
+                    reactor.register(self.inner.as_raw_id(), interest, cx.waker())
+                }) {
+                    Ok(()) => Poll::Pending,
+                    Err(e) => Poll::Ready(Err(e)),
+                }
+            }
+            res => Poll::Ready(res),
+        }
+    }
+}
+
+impl Drop for AsyncTcpStream {
+    fn drop(&mut self) {
+        let id = self.inner.as_raw_id();
+        let _ = try_with_reactor(|reactor| reactor.deregister(id));
+    }
+}
+
+impl futures_io::AsyncRead for AsyncTcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_io(cx, Interest::Read, || self.inner.read(buf))
+            .map_err(io::Error::other)
+    }
+}
+
+impl futures_io::AsyncWrite for AsyncTcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_io(cx, Interest::Write, || self.inner.write(buf))
+            .map_err(io::Error::other)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(
+            self.inner
+                .shutdown(super::Shutdown::Write)
+                .map_err(io::Error::other),
+        )
+    }
+}
@@ -0,0 +1,328 @@
+use core::{ffi::CStr, fmt, mem, net::Ipv4Addr, str::Utf8Error};
+
+use vitasdk_sys::{
+    sceNetCtlCheckCallback, sceNetCtlInetGetInfo, sceNetCtlInetGetState, sceNetCtlInit,
+    sceNetCtlTerm, SceNetCtlInfo, SceNetCtlInfoType, SCE_NETCTL_INFO_GET_DEFAULT_ROUTE,
+    SCE_NETCTL_INFO_GET_IP_ADDRESS, SCE_NETCTL_INFO_GET_NETMASK, SCE_NETCTL_INFO_GET_PRIMARY_DNS,
+    SCE_NETCTL_INFO_GET_RSSI_DBM, SCE_NETCTL_INFO_GET_RSSI_PERCENTAGE,
+    SCE_NETCTL_INFO_GET_SECONDARY_DNS, SCE_NETCTL_INFO_GET_SSID, SCE_NETCTL_INFO_SSID_LEN_MAX,
+    SCE_NETCTL_STATE_CONNECTED, SCE_NETCTL_STATE_CONNECTING, SCE_NETCTL_STATE_DISCONNECTED,
+    SCE_NETCTL_STATE_FINALIZING, SCE_NET_ERROR_EINVAL,
+};
+
+use crate::error::{sce_result_unit_from_code, SceError, SceResult};
+
+/// Network connection manager, obtained from [`GlobalState::net_ctl`].
+///
+/// Terminates the connection manager on drop.
+///
+/// [`GlobalState::net_ctl`]: super::GlobalState::net_ctl
+#[derive(Debug)]
+pub struct NetCtl {
+    _private: (),
+}
+
+impl NetCtl {
+    pub(super) fn init() -> SceResult<Self> {
+        sce_result_unit_from_code(unsafe { sceNetCtlInit() })?;
+        Ok(NetCtl { _private: () })
+    }
+
+    #[doc(alias = "sceNetCtlInetGetState")]
+    pub fn state(&self) -> SceResult<NetCtlState> {
+        let mut state = 0;
+        sce_result_unit_from_code(unsafe { sceNetCtlInetGetState(&mut state) })?;
+        NetCtlState::from_raw(state as u32).ok_or(SceError::from_error_code(SCE_NET_ERROR_EINVAL))
+    }
+
+    pub fn is_ip_obtained(&self) -> SceResult<bool> {
+        Ok(self.state()? == NetCtlState::IpObtained)
+    }
+
+    /// Queries all of the infrastructure connection information at once.
+    ///
+    /// Wi-Fi specific fields are `None` when connected over the wire.
+    pub fn info(&self) -> SceResult<NetCtlInfo> {
+        Ok(NetCtlInfo {
+            ip_address: self.ip_address()?,
+            netmask: self.netmask()?,
+            default_route: self.default_route()?,
+            primary_dns: self.primary_dns()?,
+            secondary_dns: self.secondary_dns()?,
+            ssid: self.ssid().ok(),
+            rssi_percentage: self.rssi_percentage().ok(),
+            rssi_dbm: self.rssi_dbm().ok(),
+        })
+    }
+
+    pub fn ip_address(&self) -> SceResult<Ipv4Addr> {
+        self.get_addr(SCE_NETCTL_INFO_GET_IP_ADDRESS)
+    }
+
+    pub fn netmask(&self) -> SceResult<Ipv4Addr> {
+        self.get_addr(SCE_NETCTL_INFO_GET_NETMASK)
+    }
+
+    pub fn default_route(&self) -> SceResult<Ipv4Addr> {
+        self.get_addr(SCE_NETCTL_INFO_GET_DEFAULT_ROUTE)
+    }
+
+    pub fn primary_dns(&self) -> SceResult<Ipv4Addr> {
+        self.get_addr(SCE_NETCTL_INFO_GET_PRIMARY_DNS)
+    }
+
+    pub fn secondary_dns(&self) -> SceResult<Ipv4Addr> {
+        self.get_addr(SCE_NETCTL_INFO_GET_SECONDARY_DNS)
+    }
+
+    pub fn ssid(&self) -> SceResult<Ssid> {
+        let info = self.get_info(SCE_NETCTL_INFO_GET_SSID)?;
+        let mut ssid = Ssid {
+            buf: [0; SSID_CAPACITY],
+        };
+        // SAFETY: ssid field was requested
+        for (dst, &src) in ssid.buf.iter_mut().zip(unsafe { &info.ssid }) {
+            *dst = src as u8;
+        }
+        ssid.buf[SSID_CAPACITY - 1] = 0;
+        Ok(ssid)
+    }
+
+    /// Signal strength in percents.
+    pub fn rssi_percentage(&self) -> SceResult<u32> {
+        let info = self.get_info(SCE_NETCTL_INFO_GET_RSSI_PERCENTAGE)?;
+        // SAFETY: rssi_percentage field was requested
+        Ok(unsafe { info.rssi_percentage })
+    }
+
+    /// Signal strength in decibel-milliwatts.
+    pub fn rssi_dbm(&self) -> SceResult<u32> {
+        let info = self.get_info(SCE_NETCTL_INFO_GET_RSSI_DBM)?;
+        // SAFETY: rssi_dbm field was requested
+        Ok(unsafe { info.rssi_dbm })
+    }
+
+    /// Registers `callback` to be called on connection events.
+    ///
+    /// Callbacks are only invoked from within [`NetCtl::check_callback`], on
+    /// the thread calling it. Callback is unregistered when the returned
+    /// handle is dropped.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[doc(alias = "sceNetCtlInetRegisterCallback")]
+    pub fn register_callback<F>(&self, callback: F) -> SceResult<InetCallback<'_, F>>
+    where
+        F: FnMut(NetCtlEvent) + Send + 'static,
+    {
+        InetCallback::register(callback)
+    }
+
+    /// Runs registered callbacks for the pending connection events.
+    #[doc(alias = "sceNetCtlCheckCallback")]
+    pub fn check_callback(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceNetCtlCheckCallback() })
+    }
+
+    fn get_info(&self, code: SceNetCtlInfoType) -> SceResult<SceNetCtlInfo> {
+        // SAFETY: plain old data
+        let mut info: SceNetCtlInfo = unsafe { mem::zeroed() };
+        sce_result_unit_from_code(unsafe { sceNetCtlInetGetInfo(code as i32, &mut info) })?;
+        Ok(info)
+    }
+
+    fn get_addr(&self, code: SceNetCtlInfoType) -> SceResult<Ipv4Addr> {
+        let info = self.get_info(code)?;
+        // SAFETY: every address field shares the same layout
+        let addr = unsafe { &info.ip_address };
+        let addr = unsafe { CStr::from_ptr(addr.as_ptr()) };
+        addr.to_str()
+            .ok()
+            .and_then(|addr| addr.parse().ok())
+            .ok_or(SceError::from_error_code(SCE_NET_ERROR_EINVAL))
+    }
+}
+
+impl Drop for NetCtl {
+    fn drop(&mut self) {
+        unsafe { sceNetCtlTerm() };
+    }
+}
+
+/// Infrastructure connection state.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NetCtlState {
+    Disconnected,
+    Connecting,
+    Finalizing,
+    IpObtained,
+}
+
+impl NetCtlState {
+    fn from_raw(state: u32) -> Option<Self> {
+        match state {
+            SCE_NETCTL_STATE_DISCONNECTED => Some(NetCtlState::Disconnected),
+            SCE_NETCTL_STATE_CONNECTING => Some(NetCtlState::Connecting),
+            SCE_NETCTL_STATE_FINALIZING => Some(NetCtlState::Finalizing),
+            SCE_NETCTL_STATE_CONNECTED => Some(NetCtlState::IpObtained),
+            _ => None,
+        }
+    }
+}
+
+/// Event passed into callbacks registered with [`NetCtl::register_callback`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NetCtlEvent {
+    Disconnected,
+    DisconnectRequestFinished,
+    IpObtained,
+    Other(i32),
+}
+
+impl NetCtlEvent {
+    pub fn from_raw(event_type: i32) -> Self {
+        match event_type {
+            1 => NetCtlEvent::Disconnected,
+            2 => NetCtlEvent::DisconnectRequestFinished,
+            3 => NetCtlEvent::IpObtained,
+            other => NetCtlEvent::Other(other),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct NetCtlInfo {
+    pub ip_address: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+    pub default_route: Ipv4Addr,
+    pub primary_dns: Ipv4Addr,
+    pub secondary_dns: Ipv4Addr,
+    pub ssid: Option<Ssid>,
+    pub rssi_percentage: Option<u32>,
+    pub rssi_dbm: Option<u32>,
+}
+
+const SSID_CAPACITY: usize = SCE_NETCTL_INFO_SSID_LEN_MAX as usize + 1;
+
+/// Wi-Fi network name stored inline.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Ssid {
+    buf: [u8; SSID_CAPACITY],
+}
+
+impl Ssid {
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_c_str().to_bytes()
+    }
+
+    pub fn as_c_str(&self) -> &CStr {
+        CStr::from_bytes_until_nul(&self.buf).expect("ssid is nul-terminated")
+    }
+
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        self.as_c_str().to_str()
+    }
+}
+
+impl fmt::Debug for Ssid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+impl fmt::Display for Ssid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_bytes().escape_ascii(), f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use inet_callback::InetCallback;
+
+#[cfg(feature = "alloc")]
+mod inet_callback {
+    use alloc::boxed::Box;
+    use core::{ffi::c_void, fmt, marker::PhantomData, mem, ptr, ptr::NonNull};
+
+    use vitasdk_sys::{sceNetCtlInetRegisterCallback, sceNetCtlInetUnregisterCallback};
+
+    use super::{NetCtl, NetCtlEvent};
+    use crate::error::{sce_result_unit_from_code, SceResult};
+
+    /// Registered connection event callback, see [`NetCtl::register_callback`].
+    pub struct InetCallback<'a, F> {
+        cid: i32,
+        callback: NonNull<F>,
+        _net_ctl: PhantomData<&'a NetCtl>,
+    }
+
+    impl<F> InetCallback<'_, F>
+    where
+        F: FnMut(NetCtlEvent) + Send + 'static,
+    {
+        pub(super) fn register(callback: F) -> SceResult<Self> {
+            let callback = NonNull::from(Box::leak(Box::new(callback)));
+            let mut cid = 0;
+            let res = sce_result_unit_from_code(unsafe {
+                sceNetCtlInetRegisterCallback(
+                    Some(trampoline::<F>),
+                    callback.as_ptr().cast(),
+                    &mut cid,
+                )
+            });
+            match res {
+                Ok(()) => Ok(InetCallback {
+                    cid,
+                    callback,
+                    _net_ctl: PhantomData,
+                }),
+                Err(e) => {
+                    drop(unsafe { Box::from_raw(callback.as_ptr()) });
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    impl<F> InetCallback<'_, F> {
+        /// Does the same thing as drop, but you could handle the error case.
+        pub fn unregister(self) -> SceResult<()> {
+            mem::ManuallyDrop::new(self).unregister_()
+        }
+
+        fn unregister_(&mut self) -> SceResult<()> {
+            sce_result_unit_from_code(unsafe { sceNetCtlInetUnregisterCallback(self.cid) })?;
+            // Leaked on error since the callback could still be invoked
+            drop(unsafe { Box::from_raw(self.callback.as_ptr()) });
+            Ok(())
+        }
+    }
+
+    impl<F> Drop for InetCallback<'_, F> {
+        fn drop(&mut self) {
+            let _ = self.unregister_();
+        }
+    }
+
+    impl<F> fmt::Debug for InetCallback<'_, F> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("InetCallback")
+                .field("cid", &self.cid)
+                .finish_non_exhaustive()
+        }
+    }
+
+    // SAFETY: callback itself is `Send` and is only accessed through the pointer
+    unsafe impl<F: Send> Send for InetCallback<'_, F> {}
+
+    unsafe extern "C" fn trampoline<F>(event_type: i32, arg: *mut c_void) -> *mut c_void
+    where
+        F: FnMut(NetCtlEvent),
+    {
+        let callback = unsafe { &mut *arg.cast::<F>() };
+        callback(NetCtlEvent::from_raw(event_type));
+        ptr::null_mut()
+    }
+}
@@ -0,0 +1,209 @@
+use core::{
+    ffi::{c_int, CStr},
+    fmt, mem,
+    net::{IpAddr, Ipv4Addr},
+    ptr,
+    str::Utf8Error,
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceNetResolverAbort, sceNetResolverCreate, sceNetResolverDestroy, sceNetResolverStartAton,
+    sceNetResolverStartNtoa, SceNetInAddr, SCE_NET_ERROR_EAFNOSUPPORT, SCE_NET_ERROR_EINVAL,
+    SCE_NET_ERROR_ENAMETOOLONG, SCE_NET_RESOLVER_HOSTNAME_LEN_MAX,
+};
+
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceError, SceResult};
+
+const HOSTNAME_CAPACITY: usize = SCE_NET_RESOLVER_HOSTNAME_LEN_MAX as usize + 1;
+
+/// Resolves `host` into its address using a temporary [`Resolver`].
+///
+/// The system resolver returns a single IPv4 address per query. IP address
+/// literals are returned as is without querying DNS, IPv6 ones failing with
+/// `SCE_NET_ERROR_EAFNOSUPPORT`.
+pub fn resolve(host: &str) -> SceResult<Ipv4Addr> {
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(addr)) => return Ok(addr),
+        Ok(IpAddr::V6(_)) => return Err(SceError::from_error_code(SCE_NET_ERROR_EAFNOSUPPORT)),
+        Err(_) => {}
+    }
+    let host = Hostname::from_str(host)?;
+    Resolver::new(c"SceNetResolver")?.resolve(host.as_c_str())
+}
+
+/// Looks up the host name of `addr` using a temporary [`Resolver`].
+pub fn resolve_addr(addr: IpAddr) -> SceResult<Hostname> {
+    match addr {
+        IpAddr::V4(addr) => Resolver::new(c"SceNetResolver")?.resolve_addr(addr),
+        IpAddr::V6(_) => Err(SceError::from_error_code(SCE_NET_ERROR_EAFNOSUPPORT)),
+    }
+}
+
+/// DNS resolver instance.
+///
+/// Only IPv4 addresses are supported by the system resolver.
+#[derive(Debug)]
+pub struct Resolver {
+    id: c_int,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl Resolver {
+    pub fn new(name: &CStr) -> SceResult<Self> {
+        let id = sce_result_usize_from_code(unsafe {
+            sceNetResolverCreate(name.as_ptr(), ptr::null_mut(), 0)
+        })?;
+        Ok(Resolver {
+            id: id as c_int,
+            timeout: Duration::ZERO,
+            retries: 0,
+        })
+    }
+
+    /// Sets timeout of a single query, with microsecond precision.
+    ///
+    /// Zero duration stands for the system default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many times a query is retried, zero stands for the system default.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Resolves host name into its address.
+    ///
+    /// Returns the first address record, the system resolver doesn't
+    /// provide the other ones.
+    #[doc(alias = "sceNetResolverStartNtoa")]
+    pub fn resolve(&self, host: &CStr) -> SceResult<Ipv4Addr> {
+        let mut addr = SceNetInAddr { s_addr: 0 };
+        sce_result_unit_from_code(unsafe {
+            sceNetResolverStartNtoa(
+                self.id,
+                host.as_ptr(),
+                &mut addr,
+                self.raw_timeout(),
+                self.raw_retries(),
+                0,
+            )
+        })?;
+        Ok(Ipv4Addr::from(u32::from_be(addr.s_addr)))
+    }
+
+    /// Looks up the host name of an address.
+    ///
+    /// Fails with `SCE_NET_ERROR_ENAMETOOLONG` if the name doesn't fit in a
+    /// [`Hostname`].
+    #[doc(alias = "sceNetResolverStartAton")]
+    pub fn resolve_addr(&self, addr: Ipv4Addr) -> SceResult<Hostname> {
+        let addr = SceNetInAddr {
+            s_addr: u32::from(addr).to_be(),
+        };
+        let mut hostname = Hostname::empty();
+        sce_result_unit_from_code(unsafe {
+            sceNetResolverStartAton(
+                self.id,
+                &addr,
+                hostname.buf.as_mut_ptr().cast(),
+                HOSTNAME_CAPACITY as i32,
+                self.raw_timeout(),
+                self.raw_retries(),
+                0,
+            )
+        })?;
+        // Truncated names are not guaranteed to be nul-terminated
+        hostname.len = hostname
+            .buf
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(SceError::from_error_code(SCE_NET_ERROR_ENAMETOOLONG))?;
+        Ok(hostname)
+    }
+
+    /// Aborts a query running on this resolver from another thread.
+    pub fn abort(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceNetResolverAbort(self.id, 0) })
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceNetResolverDestroy(self.id) })
+    }
+
+    fn raw_timeout(&self) -> i32 {
+        self.timeout.as_micros().min(i32::MAX as u128) as i32
+    }
+
+    fn raw_retries(&self) -> i32 {
+        self.retries.min(i32::MAX as u32) as i32
+    }
+}
+
+impl Drop for Resolver {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
+
+/// Nul-terminated host name stored inline.
+#[derive(Clone)]
+pub struct Hostname {
+    buf: [u8; HOSTNAME_CAPACITY],
+    len: usize,
+}
+
+impl Hostname {
+    const fn empty() -> Self {
+        Hostname {
+            buf: [0; HOSTNAME_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn from_str(host: &str) -> SceResult<Self> {
+        if host.len() >= HOSTNAME_CAPACITY {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_ENAMETOOLONG));
+        }
+        if host.as_bytes().contains(&0) {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_EINVAL));
+        }
+        let mut hostname = Hostname::empty();
+        hostname.buf[..host.len()].copy_from_slice(host.as_bytes());
+        hostname.len = host.len();
+        Ok(hostname)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn as_c_str(&self) -> &CStr {
+        CStr::from_bytes_with_nul(&self.buf[..=self.len]).expect("hostname is nul-terminated")
+    }
+
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(self.as_bytes())
+    }
+}
+
+impl fmt::Debug for Hostname {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+impl fmt::Display for Hostname {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_bytes().escape_ascii(), f)
+    }
+}
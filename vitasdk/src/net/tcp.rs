@@ -0,0 +1,268 @@
+use core::{
+    ffi::{c_int, c_void, CStr},
+    mem,
+    net::{Ipv4Addr, SocketAddrV4},
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceNetConnect, sceNetGetpeername, sceNetGetsockname, sceNetGetsockopt, sceNetRecv, sceNetSend,
+    sceNetSetsockopt, sceNetShutdown, sceNetSocket, sceNetSocketAbort, sceNetSocketClose,
+    SceNetInAddr, SceNetSockaddr, SceNetSockaddrIn, SCE_NET_AF_INET, SCE_NET_ERROR_EPIPE,
+    SCE_NET_IPPROTO_TCP, SCE_NET_SHUT_RD, SCE_NET_SHUT_RDWR, SCE_NET_SHUT_WR, SCE_NET_SOCK_STREAM,
+    SCE_NET_SOL_SOCKET, SCE_NET_SO_ERROR, SCE_NET_SO_NBIO, SCE_NET_SO_RCVTIMEO,
+    SCE_NET_SO_SNDTIMEO, SCE_NET_TCP_NODELAY,
+};
+
+use super::resolve;
+use crate::error::{sce_result_unit_from_code, sce_result_usize_from_code, SceError, SceResult};
+
+/// Base of the `SCE_NET_ERROR_*` codes, combined with an errno.
+const NET_ERROR_BASE: u32 = 0x80410100;
+
+/// TCP connection over IPv4.
+#[derive(Debug)]
+pub struct TcpStream {
+    id: c_int,
+}
+
+impl TcpStream {
+    #[doc(alias = "sceNetConnect")]
+    pub fn connect(addr: SocketAddrV4) -> SceResult<Self> {
+        let stream = TcpStream::new(c"SceNetTcpStream")?;
+        let addr = sockaddr_from(addr);
+        sce_result_unit_from_code(unsafe {
+            sceNetConnect(
+                stream.id,
+                (&addr as *const SceNetSockaddrIn).cast::<SceNetSockaddr>(),
+                mem::size_of::<SceNetSockaddrIn>() as u32,
+            )
+        })?;
+        Ok(stream)
+    }
+
+    /// Resolves `host` and connects to its address.
+    pub fn connect_to(host: &str, port: u16) -> SceResult<Self> {
+        TcpStream::connect(SocketAddrV4::new(resolve(host)?, port))
+    }
+
+    /// Creates a socket, which is not connected yet.
+    pub(super) fn new(name: &CStr) -> SceResult<Self> {
+        let id = sce_result_usize_from_code(unsafe {
+            sceNetSocket(
+                name.as_ptr(),
+                SCE_NET_AF_INET as i32,
+                SCE_NET_SOCK_STREAM as i32,
+                SCE_NET_IPPROTO_TCP as i32,
+            )
+        })?;
+        Ok(TcpStream { id: id as c_int })
+    }
+
+    /// Returns socket id to be used with raw `sceNet*` functions.
+    pub fn as_raw_id(&self) -> c_int {
+        self.id
+    }
+
+    #[doc(alias = "sceNetRecv")]
+    pub fn read(&self, buf: &mut [u8]) -> SceResult<usize> {
+        sce_result_usize_from_code(unsafe {
+            sceNetRecv(
+                self.id,
+                buf.as_mut_ptr().cast(),
+                buf.len().min(i32::MAX as usize) as u32,
+                0,
+            )
+        })
+    }
+
+    #[doc(alias = "sceNetSend")]
+    pub fn write(&self, buf: &[u8]) -> SceResult<usize> {
+        sce_result_usize_from_code(unsafe {
+            sceNetSend(
+                self.id,
+                buf.as_ptr().cast(),
+                buf.len().min(i32::MAX as usize) as u32,
+                0,
+            )
+        })
+    }
+
+    /// Writes the whole buffer, failing with `SCE_NET_ERROR_EPIPE` if the
+    /// socket stops accepting data.
+    pub fn write_all(&self, mut buf: &[u8]) -> SceResult<()> {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return Err(SceError::from_error_code(SCE_NET_ERROR_EPIPE)),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    #[doc(alias = "sceNetShutdown")]
+    pub fn shutdown(&self, how: Shutdown) -> SceResult<()> {
+        let how = match how {
+            Shutdown::Read => SCE_NET_SHUT_RD,
+            Shutdown::Write => SCE_NET_SHUT_WR,
+            Shutdown::Both => SCE_NET_SHUT_RDWR,
+        };
+        sce_result_unit_from_code(unsafe { sceNetShutdown(self.id, how as i32) })
+    }
+
+    /// Aborts blocking operations running on this socket from another thread.
+    #[doc(alias = "sceNetSocketAbort")]
+    pub fn abort(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceNetSocketAbort(self.id, 0) })
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> SceResult<()> {
+        self.set_option(SCE_NET_SOL_SOCKET, SCE_NET_SO_NBIO, nonblocking as c_int)
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> SceResult<()> {
+        self.set_option(SCE_NET_IPPROTO_TCP, SCE_NET_TCP_NODELAY, nodelay as c_int)
+    }
+
+    /// Sets timeout with microsecond precision, `None` blocks indefinitely.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> SceResult<()> {
+        self.set_option(
+            SCE_NET_SOL_SOCKET,
+            SCE_NET_SO_RCVTIMEO,
+            timeout_to_usec(timeout),
+        )
+    }
+
+    /// Sets timeout with microsecond precision, `None` blocks indefinitely.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> SceResult<()> {
+        self.set_option(
+            SCE_NET_SOL_SOCKET,
+            SCE_NET_SO_SNDTIMEO,
+            timeout_to_usec(timeout),
+        )
+    }
+
+    /// Returns and clears pending error of the socket.
+    pub fn take_error(&self) -> SceResult<Option<SceError>> {
+        let mut error: c_int = 0;
+        let mut len = mem::size_of::<c_int>() as u32;
+        sce_result_unit_from_code(unsafe {
+            sceNetGetsockopt(
+                self.id,
+                SCE_NET_SOL_SOCKET as i32,
+                SCE_NET_SO_ERROR as i32,
+                (&mut error as *mut c_int).cast(),
+                &mut len,
+            )
+        })?;
+        // Pending error is a positive errno
+        Ok((error != 0).then(|| SceError::from_error_code(NET_ERROR_BASE | error as u32)))
+    }
+
+    #[doc(alias = "sceNetGetpeername")]
+    pub fn peer_addr(&self) -> SceResult<SocketAddrV4> {
+        let mut addr = sockaddr_from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let mut len = mem::size_of::<SceNetSockaddrIn>() as u32;
+        sce_result_unit_from_code(unsafe {
+            sceNetGetpeername(
+                self.id,
+                (&mut addr as *mut SceNetSockaddrIn).cast::<SceNetSockaddr>(),
+                &mut len,
+            )
+        })?;
+        Ok(sockaddr_to(&addr))
+    }
+
+    #[doc(alias = "sceNetGetsockname")]
+    pub fn local_addr(&self) -> SceResult<SocketAddrV4> {
+        let mut addr = sockaddr_from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let mut len = mem::size_of::<SceNetSockaddrIn>() as u32;
+        sce_result_unit_from_code(unsafe {
+            sceNetGetsockname(
+                self.id,
+                (&mut addr as *mut SceNetSockaddrIn).cast::<SceNetSockaddr>(),
+                &mut len,
+            )
+        })?;
+        Ok(sockaddr_to(&addr))
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn close(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).close_()
+    }
+
+    fn close_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceNetSocketClose(self.id) })
+    }
+
+    fn set_option(&self, level: u32, option: u32, value: c_int) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceNetSetsockopt(
+                self.id,
+                level as i32,
+                option as i32,
+                (&value as *const c_int).cast::<c_void>(),
+                mem::size_of::<c_int>() as u32,
+            )
+        })
+    }
+}
+
+impl Drop for TcpStream {
+    fn drop(&mut self) {
+        let _ = self.close_();
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        TcpStream::read(self, buf).map_err(std::io::Error::other)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        TcpStream::write(self, buf).map_err(std::io::Error::other)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Directions to shut down with [`TcpStream::shutdown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shutdown {
+    Read,
+    Write,
+    Both,
+}
+
+pub(super) fn sockaddr_from(addr: SocketAddrV4) -> SceNetSockaddrIn {
+    SceNetSockaddrIn {
+        sin_len: mem::size_of::<SceNetSockaddrIn>() as u8,
+        sin_family: SCE_NET_AF_INET as u8,
+        sin_port: addr.port().to_be(),
+        sin_addr: SceNetInAddr {
+            s_addr: u32::from(*addr.ip()).to_be(),
+        },
+        sin_vport: 0,
+        sin_zero: [0; 6],
+    }
+}
+
+fn sockaddr_to(addr: &SceNetSockaddrIn) -> SocketAddrV4 {
+    SocketAddrV4::new(
+        Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)),
+        u16::from_be(addr.sin_port),
+    )
+}
+
+fn timeout_to_usec(timeout: Option<Duration>) -> c_int {
+    timeout.map_or(0, |timeout| {
+        timeout.as_micros().clamp(1, i32::MAX as u128) as c_int
+    })
+}
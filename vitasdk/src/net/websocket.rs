@@ -0,0 +1,377 @@
+//! [RFC 6455](https://www.rfc-editor.org/rfc/rfc6455) WebSocket client over
+//! [`TcpStream`].
+//!
+//! Only `ws://` URLs are supported. The system SSL library only provides
+//! certificate handling to the HTTP library and has no API to run TLS over a
+//! socket, so `wss://` URLs fail with `SCE_NET_ERROR_EPROTONOSUPPORT`.
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use vitasdk_sys::{
+    sceKernelGetRandomNumber, SCE_NET_ERROR_ECONNRESET, SCE_NET_ERROR_EINVAL,
+    SCE_NET_ERROR_EMSGSIZE, SCE_NET_ERROR_ENOTCONN,
+};
+
+use super::TcpStream;
+use crate::{
+    error::{sce_result_unit_from_code, SceError, SceResult},
+    proto::{
+        base64,
+        websocket::{
+            apply_mask, check_request_target, check_response, encode_frame, parse_url, FrameHeader,
+            BAD_MESSAGE, MAX_CONTROL_PAYLOAD, OP_BINARY, OP_CLOSE, OP_CONTINUATION, OP_PING,
+            OP_PONG, OP_TEXT,
+        },
+    },
+};
+
+const MAX_HANDSHAKE_SIZE: usize = 0x2000;
+const READ_BUF_SIZE: usize = 0x1000;
+
+/// Message sent or received over a [`WebSocket`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close(Option<CloseFrame>),
+}
+
+/// Status code and reason of a close message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CloseFrame {
+    pub code: u16,
+    pub reason: String,
+}
+
+impl CloseFrame {
+    pub const NORMAL: u16 = 1000;
+    pub const GOING_AWAY: u16 = 1001;
+    pub const PROTOCOL_ERROR: u16 = 1002;
+
+    pub fn new(code: u16, reason: impl Into<String>) -> Self {
+        CloseFrame {
+            code,
+            reason: reason.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Open,
+    CloseSent,
+    Closed,
+}
+
+/// Client side of a WebSocket connection.
+#[derive(Debug)]
+pub struct WebSocket {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    pos: usize,
+    fragments: Option<(u8, Vec<u8>)>,
+    max_message_size: usize,
+    max_frame_size: usize,
+    state: State,
+}
+
+impl WebSocket {
+    pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 0x1000000;
+
+    /// Connects to a `ws://host[:port][/path]` URL.
+    pub fn connect(url: &str) -> SceResult<Self> {
+        WebSocket::connect_with_headers(url, &[])
+    }
+
+    /// Connects to a `ws://host[:port][/path]` URL, sending additional
+    /// headers such as `Sec-WebSocket-Protocol` with the handshake.
+    pub fn connect_with_headers(url: &str, headers: &[(&str, &str)]) -> SceResult<Self> {
+        let (authority, host, port, path) = parse_url(url)?;
+        let stream = TcpStream::connect_to(host, port)?;
+        WebSocket::handshake(stream, authority, &path, headers)
+    }
+
+    /// Performs the opening handshake over an already connected stream.
+    ///
+    /// `host` is sent as the `Host` header and `path` as the request target.
+    /// Empty `host` or `path`, or ones containing spaces or control characters,
+    /// header names containing `:`, or names and values containing CR or LF
+    /// fail with `SCE_NET_ERROR_EINVAL`.
+    pub fn handshake(
+        stream: TcpStream,
+        host: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+    ) -> SceResult<Self> {
+        check_request_target(host, path)?;
+        let mut key = String::new();
+        base64::encode(&random_bytes::<16>()?, &mut key);
+
+        let mut request = format!(
+            "GET {path} HTTP/1.1\r\n\
+             Host: {host}\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Key: {key}\r\n\
+             Sec-WebSocket-Version: 13\r\n"
+        );
+        for (name, value) in headers {
+            if name.contains([':', '\r', '\n']) || value.contains(['\r', '\n']) {
+                return Err(SceError::from_error_code(SCE_NET_ERROR_EINVAL));
+            }
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = Vec::new();
+        let mut chunk = [0; 0x400];
+        let head_len = loop {
+            if let Some(i) = response.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+            if response.len() > MAX_HANDSHAKE_SIZE {
+                return Err(BAD_MESSAGE);
+            }
+            let n = read_some(&stream, &mut chunk)?;
+            response.extend_from_slice(&chunk[..n]);
+        };
+        check_response(&response[..head_len], &key)?;
+        response.drain(..head_len);
+
+        Ok(WebSocket {
+            stream,
+            buf: response,
+            pos: 0,
+            fragments: None,
+            max_message_size: Self::DEFAULT_MAX_MESSAGE_SIZE,
+            max_frame_size: usize::MAX,
+            state: State::Open,
+        })
+    }
+
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Limits size of received messages, larger ones fail with
+    /// `SCE_NET_ERROR_EMSGSIZE`.
+    pub fn set_max_message_size(&mut self, size: usize) {
+        self.max_message_size = size;
+    }
+
+    /// Splits sent text and binary messages into fragments of at most `size`
+    /// bytes, values below one are treated as one.
+    pub fn set_max_frame_size(&mut self, size: usize) {
+        self.max_frame_size = size.max(1);
+    }
+
+    /// Sending anything but a pong after a close message fails with
+    /// `SCE_NET_ERROR_ENOTCONN`.
+    pub fn send(&mut self, message: &Message) -> SceResult<()> {
+        let closed = match message {
+            Message::Pong(_) => self.state == State::Closed,
+            _ => self.state != State::Open,
+        };
+        if closed {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_ENOTCONN));
+        }
+        match message {
+            Message::Text(text) => self.write_data(OP_TEXT, text.as_bytes()),
+            Message::Binary(data) => self.write_data(OP_BINARY, data),
+            Message::Ping(payload) => self.write_control(OP_PING, payload),
+            Message::Pong(payload) => self.write_control(OP_PONG, payload),
+            Message::Close(frame) => {
+                let payload = match frame {
+                    Some(frame) => {
+                        let mut payload = Vec::with_capacity(2 + frame.reason.len());
+                        payload.extend_from_slice(&frame.code.to_be_bytes());
+                        payload.extend_from_slice(frame.reason.as_bytes());
+                        payload
+                    }
+                    None => Vec::new(),
+                };
+                self.write_control(OP_CLOSE, &payload)?;
+                self.state = State::CloseSent;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn send_text(&mut self, text: &str) -> SceResult<()> {
+        match self.state {
+            State::Open => self.write_data(OP_TEXT, text.as_bytes()),
+            _ => Err(SceError::from_error_code(SCE_NET_ERROR_ENOTCONN)),
+        }
+    }
+
+    pub fn send_binary(&mut self, data: &[u8]) -> SceResult<()> {
+        match self.state {
+            State::Open => self.write_data(OP_BINARY, data),
+            _ => Err(SceError::from_error_code(SCE_NET_ERROR_ENOTCONN)),
+        }
+    }
+
+    /// Payload is limited to 125 bytes.
+    pub fn ping(&mut self, payload: &[u8]) -> SceResult<()> {
+        match self.state {
+            State::Open => self.write_control(OP_PING, payload),
+            _ => Err(SceError::from_error_code(SCE_NET_ERROR_ENOTCONN)),
+        }
+    }
+
+    /// Starts the closing handshake, [`WebSocket::read`] should then be
+    /// called until it returns [`Message::Close`].
+    pub fn close(&mut self, frame: Option<CloseFrame>) -> SceResult<()> {
+        self.send(&Message::Close(frame))
+    }
+
+    /// Reads the next message, reassembling fragmented ones.
+    ///
+    /// Pings are answered automatically and close messages are echoed back
+    /// before being returned. Reading after a close message was received
+    /// fails with `SCE_NET_ERROR_ENOTCONN`.
+    pub fn read(&mut self) -> SceResult<Message> {
+        if self.state == State::Closed {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_ENOTCONN));
+        }
+        loop {
+            let (fin, opcode, payload) = self.read_frame()?;
+            match opcode {
+                OP_CONTINUATION => {
+                    let Some((_, data)) = &mut self.fragments else {
+                        return Err(BAD_MESSAGE);
+                    };
+                    if data.len() + payload.len() > self.max_message_size {
+                        return Err(SceError::from_error_code(SCE_NET_ERROR_EMSGSIZE));
+                    }
+                    data.extend_from_slice(&payload);
+                    if fin {
+                        let (opcode, data) = self.fragments.take().unwrap();
+                        return data_message(opcode, data);
+                    }
+                }
+                OP_TEXT | OP_BINARY => {
+                    if self.fragments.is_some() {
+                        return Err(BAD_MESSAGE);
+                    }
+                    if fin {
+                        return data_message(opcode, payload);
+                    }
+                    self.fragments = Some((opcode, payload));
+                }
+                OP_PING => {
+                    if self.state == State::Open {
+                        self.write_control(OP_PONG, &payload)?;
+                    }
+                    return Ok(Message::Ping(payload));
+                }
+                OP_PONG => return Ok(Message::Pong(payload)),
+                OP_CLOSE => {
+                    let frame = match payload.len() {
+                        0 => None,
+                        1 => return Err(BAD_MESSAGE),
+                        _ => Some(CloseFrame {
+                            code: u16::from_be_bytes([payload[0], payload[1]]),
+                            reason: String::from_utf8(payload[2..].to_vec())
+                                .map_err(|_| BAD_MESSAGE)?,
+                        }),
+                    };
+                    if self.state == State::Open {
+                        let code = frame.as_ref().map(|frame| frame.code.to_be_bytes());
+                        self.write_control(OP_CLOSE, code.as_ref().map_or(&[], |c| c))?;
+                    }
+                    self.state = State::Closed;
+                    return Ok(Message::Close(frame));
+                }
+                _ => return Err(BAD_MESSAGE),
+            }
+        }
+    }
+
+    fn read_frame(&mut self) -> SceResult<(bool, u8, Vec<u8>)> {
+        let header = FrameHeader::decode(|out| self.read_exact(out))?;
+        if header.len > self.max_message_size as u64 {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_EMSGSIZE));
+        }
+        let mut payload = vec![0; header.len as usize];
+        self.read_exact(&mut payload)?;
+        if let Some(mask) = header.mask {
+            apply_mask(&mut payload, mask);
+        }
+        Ok((header.fin, header.opcode, payload))
+    }
+
+    fn read_exact(&mut self, mut out: &mut [u8]) -> SceResult<()> {
+        while !out.is_empty() {
+            if self.pos == self.buf.len() {
+                if out.len() >= READ_BUF_SIZE {
+                    let n = read_some(&self.stream, out)?;
+                    out = &mut out[n..];
+                    continue;
+                }
+                self.buf.resize(READ_BUF_SIZE, 0);
+                let n = read_some(&self.stream, &mut self.buf)?;
+                self.buf.truncate(n);
+                self.pos = 0;
+            }
+            let n = out.len().min(self.buf.len() - self.pos);
+            out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+            self.pos += n;
+            out = &mut out[n..];
+        }
+        Ok(())
+    }
+
+    fn write_data(&mut self, opcode: u8, data: &[u8]) -> SceResult<()> {
+        if data.is_empty() {
+            return self.write_frame(true, opcode, data);
+        }
+        let mut chunks = data.chunks(self.max_frame_size).peekable();
+        let mut opcode = opcode;
+        while let Some(chunk) = chunks.next() {
+            self.write_frame(chunks.peek().is_none(), opcode, chunk)?;
+            opcode = OP_CONTINUATION;
+        }
+        Ok(())
+    }
+
+    fn write_control(&mut self, opcode: u8, payload: &[u8]) -> SceResult<()> {
+        if payload.len() > MAX_CONTROL_PAYLOAD {
+            return Err(SceError::from_error_code(SCE_NET_ERROR_EMSGSIZE));
+        }
+        self.write_frame(true, opcode, payload)
+    }
+
+    fn write_frame(&mut self, fin: bool, opcode: u8, payload: &[u8]) -> SceResult<()> {
+        let frame = encode_frame(fin, opcode, payload, random_bytes::<4>()?);
+        self.stream.write_all(&frame)
+    }
+}
+
+fn data_message(opcode: u8, data: Vec<u8>) -> SceResult<Message> {
+    match opcode {
+        OP_TEXT => String::from_utf8(data)
+            .map(Message::Text)
+            .map_err(|_| BAD_MESSAGE),
+        _ => Ok(Message::Binary(data)),
+    }
+}
+
+/// Like [`TcpStream::read`], but fails if the connection was closed.
+fn read_some(stream: &TcpStream, buf: &mut [u8]) -> SceResult<usize> {
+    match stream.read(buf)? {
+        0 => Err(SceError::from_error_code(SCE_NET_ERROR_ECONNRESET)),
+        n => Ok(n),
+    }
+}
+
+fn random_bytes<const N: usize>() -> SceResult<[u8; N]> {
+    let mut bytes = [0; N];
+    sce_result_unit_from_code(unsafe {
+        sceKernelGetRandomNumber(bytes.as_mut_ptr().cast(), N as u32)
+    })?;
+    Ok(bytes)
+}
@@ -0,0 +1,11 @@
+//! Protocol helpers which don't call any system function, so they are unit
+//! tested on the host.
+
+#[cfg(any(all(feature = "http", feature = "alloc"), feature = "websocket", test))]
+pub(crate) mod base64;
+#[cfg(any(feature = "http", test))]
+pub(crate) mod http;
+#[cfg(any(feature = "websocket", test))]
+pub(crate) mod sha1;
+#[cfg(any(feature = "websocket", test))]
+pub(crate) mod websocket;
@@ -0,0 +1,252 @@
+#[cfg(any(feature = "std", test))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(any(feature = "std", test))]
+use std::io::{self, BufRead, Write};
+
+#[cfg(any(feature = "alloc", test))]
+use vitasdk_sys::SCE_HTTP_ERROR_INVALID_URL;
+
+#[cfg(any(feature = "alloc", test))]
+use crate::error::{SceError, SceResult};
+
+#[cfg(any(feature = "alloc", test))]
+pub(crate) fn invalid_url() -> SceError {
+    SceError::from_error_code(SCE_HTTP_ERROR_INVALID_URL)
+}
+
+/// Returns `scheme://authority` part of the url.
+#[cfg(any(feature = "alloc", test))]
+pub(crate) fn origin(url: &str) -> SceResult<&str> {
+    let scheme_end = url.find("://").ok_or_else(invalid_url)? + "://".len();
+    let authority_len = url[scheme_end..]
+        .find(['/', '?', '#'])
+        .unwrap_or(url.len() - scheme_end);
+    if authority_len == 0 {
+        return Err(invalid_url());
+    }
+    Ok(&url[..scheme_end + authority_len])
+}
+
+/// Splits raw response headers into trimmed names and values, skipping the
+/// status line.
+#[cfg(any(feature = "alloc", test))]
+pub(crate) fn parse_headers(raw: &[u8]) -> impl Iterator<Item = (&str, &str)> {
+    raw.split(|&b| b == b'\n')
+        .filter_map(|line| core::str::from_utf8(line).ok())
+        .filter(|line| !line.starts_with("HTTP/"))
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        })
+}
+
+/// Returns position of the first byte of a `Content-Range: bytes` header.
+#[cfg(any(feature = "std", test))]
+pub(crate) fn content_range_start(value: &str) -> Option<u64> {
+    let (unit, range) = value.trim().split_once(' ')?;
+    let (start, _) = range.trim_start().split_once('-')?;
+    match unit.eq_ignore_ascii_case("bytes") {
+        true => start.parse().ok(),
+        false => None,
+    }
+}
+
+/// Formats hexadecimal chunk size followed by CRLF.
+pub(crate) fn chunk_size_line(buf: &mut [u8; 18], size: usize) -> &[u8] {
+    let digits = (usize::BITS - size.leading_zeros()).div_ceil(4).max(1) as usize;
+    for (i, b) in buf[..digits].iter_mut().enumerate() {
+        let nibble = (size >> ((digits - 1 - i) * 4)) & 0xf;
+        *b = b"0123456789abcdef"[nibble];
+    }
+    buf[digits..digits + 2].copy_from_slice(b"\r\n");
+    &buf[..digits + 2]
+}
+
+/// Received `Set-Cookie` headers along with the url they came from.
+#[cfg(any(feature = "std", test))]
+#[derive(Debug, Default)]
+pub(crate) struct Cookies(Vec<StoredCookie>);
+
+#[cfg(any(feature = "std", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StoredCookie {
+    url: String,
+    set_cookie: String,
+}
+
+#[cfg(any(feature = "std", test))]
+impl StoredCookie {
+    fn name(&self) -> &str {
+        self.set_cookie
+            .split_once('=')
+            .map_or("", |(name, _)| name.trim())
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl Cookies {
+    /// Records a cookie, replacing a cookie with the same name received from the same url.
+    pub(crate) fn insert(&mut self, url: &str, set_cookie: &str) {
+        let cookie = StoredCookie {
+            url: url.to_string(),
+            set_cookie: set_cookie.trim().to_string(),
+        };
+        match self
+            .0
+            .iter_mut()
+            .find(|c| c.url == cookie.url && c.name() == cookie.name())
+        {
+            Some(c) => *c = cookie,
+            None => self.0.push(cookie),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Iterates over urls and `Set-Cookie` headers.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|c| (c.url.as_str(), c.set_cookie.as_str()))
+    }
+
+    /// Writes cookies as lines of url and `Set-Cookie` header separated by a tab.
+    pub(crate) fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for (url, set_cookie) in self.iter() {
+            writeln!(writer, "{url}\t{set_cookie}")?;
+        }
+        writer.flush()
+    }
+
+    /// Reads cookies written by [`Cookies::save`], skipping malformed lines.
+    pub(crate) fn load<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut cookies = Cookies::default();
+        for line in reader.lines() {
+            if let Some((url, set_cookie)) = line?.split_once('\t') {
+                cookies.insert(url, set_cookie);
+            }
+        }
+        Ok(cookies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{
+        chunk_size_line, content_range_start, invalid_url, origin, parse_headers, Cookies,
+    };
+
+    #[test]
+    fn origin_of_urls() {
+        assert_eq!(origin("http://example.com"), Ok("http://example.com"));
+        assert_eq!(
+            origin("https://example.com:8443/path?query"),
+            Ok("https://example.com:8443")
+        );
+        assert_eq!(origin("http://example.com?q=1"), Ok("http://example.com"));
+        assert_eq!(origin("http://example.com#top"), Ok("http://example.com"));
+        assert_eq!(origin("example.com/path"), Err(invalid_url()));
+        assert_eq!(origin("http:///path"), Err(invalid_url()));
+    }
+
+    #[test]
+    fn headers_skip_status_line() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nX-Empty:\r\n\
+                    Location:  http://example.com/a:b \r\nmalformed\r\n\r\n";
+        let headers: Vec<_> = parse_headers(raw).collect();
+        assert_eq!(
+            headers,
+            [
+                ("Content-Type", "text/plain"),
+                ("X-Empty", ""),
+                ("Location", "http://example.com/a:b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_range_starts() {
+        assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+        assert_eq!(content_range_start(" bytes 0-0/*"), Some(0));
+        assert_eq!(content_range_start("Bytes 42-99/100"), Some(42));
+        assert_eq!(content_range_start("bytes */200"), None);
+        assert_eq!(content_range_start("items 1-2/3"), None);
+        assert_eq!(content_range_start("bytes x-2/3"), None);
+    }
+
+    #[test]
+    fn chunk_size_lines() {
+        let mut buf = [0; 18];
+        assert_eq!(chunk_size_line(&mut buf, 0), b"0\r\n");
+        assert_eq!(chunk_size_line(&mut buf, 0xf), b"f\r\n");
+        assert_eq!(chunk_size_line(&mut buf, 0x10), b"10\r\n");
+        assert_eq!(chunk_size_line(&mut buf, 0x1a2b), b"1a2b\r\n");
+        let max = chunk_size_line(&mut buf, usize::MAX);
+        assert_eq!(max.len(), usize::BITS as usize / 4 + 2);
+        assert!(max.starts_with(b"ffff") && max.ends_with(b"f\r\n"));
+    }
+
+    #[test]
+    fn cookie_replaced_by_name_and_url() {
+        let mut cookies = Cookies::default();
+        cookies.insert("http://a.com", "id=1; Path=/");
+        cookies.insert("http://a.com", " theme=dark ");
+        cookies.insert("http://b.com", "id=2");
+        cookies.insert("http://a.com", "id = 3");
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(
+            cookies.iter().collect::<Vec<_>>(),
+            [
+                ("http://a.com", "id = 3"),
+                ("http://a.com", "theme=dark"),
+                ("http://b.com", "id=2"),
+            ]
+        );
+        cookies.clear();
+        assert!(cookies.is_empty());
+    }
+
+    #[test]
+    fn cookies_save_and_load() {
+        let mut cookies = Cookies::default();
+        cookies.insert("http://a.com", "id=1; Path=/; HttpOnly");
+        cookies.insert("http://b.com/path", "session=abc");
+
+        let mut saved = Vec::new();
+        cookies.save(&mut saved).unwrap();
+        assert_eq!(
+            saved,
+            b"http://a.com\tid=1; Path=/; HttpOnly\nhttp://b.com/path\tsession=abc\n"
+        );
+
+        let loaded = Cookies::load(&saved[..]).unwrap();
+        assert_eq!(
+            loaded.iter().collect::<Vec<_>>(),
+            cookies.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cookies_load_skips_malformed_lines() {
+        let loaded = Cookies::load(&b"no tab here\n\nhttp://a.com\tid=1\n"[..]).unwrap();
+        assert_eq!(
+            loaded.iter().collect::<Vec<_>>(),
+            [("http://a.com", "id=1")]
+        );
+    }
+}
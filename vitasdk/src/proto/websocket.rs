@@ -0,0 +1,400 @@
+use alloc::{format, string::String, vec::Vec};
+
+use vitasdk_sys::{
+    SCE_NET_ERROR_EBADMSG, SCE_NET_ERROR_ECONNREFUSED, SCE_NET_ERROR_EINVAL,
+    SCE_NET_ERROR_EPROTONOSUPPORT,
+};
+
+use crate::{
+    error::{SceError, SceResult},
+    proto::{base64, sha1::sha1},
+};
+
+pub(crate) const OP_CONTINUATION: u8 = 0x0;
+pub(crate) const OP_TEXT: u8 = 0x1;
+pub(crate) const OP_BINARY: u8 = 0x2;
+pub(crate) const OP_CLOSE: u8 = 0x8;
+pub(crate) const OP_PING: u8 = 0x9;
+pub(crate) const OP_PONG: u8 = 0xa;
+
+pub(crate) const MAX_CONTROL_PAYLOAD: usize = 125;
+
+pub(crate) const BAD_MESSAGE: SceError = SceError::from_error_code(SCE_NET_ERROR_EBADMSG);
+
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Splits URL into authority, host, port and request target.
+pub(crate) fn parse_url(url: &str) -> SceResult<(&str, &str, u16, String)> {
+    let Some(rest) = url.strip_prefix("ws://") else {
+        return Err(SceError::from_error_code(match url.starts_with("wss://") {
+            true => SCE_NET_ERROR_EPROTONOSUPPORT,
+            false => SCE_NET_ERROR_EINVAL,
+        }));
+    };
+    let (authority, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let path = path.split('#').next().unwrap_or_default();
+    let path = match path.starts_with('/') {
+        true => String::from(path),
+        false => format!("/{path}"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| SceError::from_error_code(SCE_NET_ERROR_EINVAL))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(SceError::from_error_code(SCE_NET_ERROR_EINVAL));
+    }
+    check_request_target(authority, &path)?;
+    Ok((authority, host, port, path))
+}
+
+/// Fails with `SCE_NET_ERROR_EINVAL` if `host` or `path` are empty or contain
+/// spaces or control characters, which would let them add lines to the request.
+pub(crate) fn check_request_target(host: &str, path: &str) -> SceResult<()> {
+    let invalid = |s: &str| s.is_empty() || s.bytes().any(|b| b <= b' ' || b == 0x7f);
+    match invalid(host) || invalid(path) {
+        true => Err(SceError::from_error_code(SCE_NET_ERROR_EINVAL)),
+        false => Ok(()),
+    }
+}
+
+/// Checks the handshake response head against the key sent with the request.
+pub(crate) fn check_response(head: &[u8], key: &str) -> SceResult<()> {
+    let head = core::str::from_utf8(head).map_err(|_| BAD_MESSAGE)?;
+    let mut lines = head.split("\r\n");
+    let mut status = lines.next().unwrap_or_default().split(' ');
+    if !status.next().unwrap_or_default().starts_with("HTTP/") {
+        return Err(BAD_MESSAGE);
+    }
+    if status.next() != Some("101") {
+        return Err(SceError::from_error_code(SCE_NET_ERROR_ECONNREFUSED));
+    }
+
+    let mut accept = String::new();
+    base64::encode(&sha1(format!("{key}{ACCEPT_GUID}").as_bytes()), &mut accept);
+    let (mut upgrade, mut connection, mut accepted) = (false, false, false);
+    for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("upgrade") {
+            upgrade = value.eq_ignore_ascii_case("websocket");
+        } else if name.eq_ignore_ascii_case("connection") {
+            connection = value
+                .split(',')
+                .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+        } else if name.eq_ignore_ascii_case("sec-websocket-accept") {
+            accepted = value == accept;
+        }
+    }
+    match upgrade && connection && accepted {
+        true => Ok(()),
+        false => Err(BAD_MESSAGE),
+    }
+}
+
+/// Header of a received frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameHeader {
+    pub(crate) fin: bool,
+    pub(crate) opcode: u8,
+    pub(crate) len: u64,
+    pub(crate) mask: Option<[u8; 4]>,
+}
+
+impl FrameHeader {
+    /// Decodes a frame header, reading its bytes with `read_exact`.
+    ///
+    /// Frames with reserved bits set and fragmented or oversized control
+    /// frames fail with `SCE_NET_ERROR_EBADMSG`.
+    pub(crate) fn decode(
+        mut read_exact: impl FnMut(&mut [u8]) -> SceResult<()>,
+    ) -> SceResult<Self> {
+        let mut head = [0; 2];
+        read_exact(&mut head)?;
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0f;
+        if head[0] & 0x70 != 0 {
+            return Err(BAD_MESSAGE);
+        }
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut len = [0; 2];
+                read_exact(&mut len)?;
+                u16::from_be_bytes(len) as u64
+            }
+            127 => {
+                let mut len = [0; 8];
+                read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => len as u64,
+        };
+        if opcode & 0x8 != 0 && (!fin || len > MAX_CONTROL_PAYLOAD as u64) {
+            return Err(BAD_MESSAGE);
+        }
+        // Servers must not mask frames, but unmasking costs nothing
+        let mask = match head[1] & 0x80 != 0 {
+            true => {
+                let mut mask = [0; 4];
+                read_exact(&mut mask)?;
+                Some(mask)
+            }
+            false => None,
+        };
+        Ok(FrameHeader {
+            fin,
+            opcode,
+            len,
+            mask,
+        })
+    }
+}
+
+/// Encodes a client frame, masking `payload` with `mask`.
+pub(crate) fn encode_frame(fin: bool, opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(14 + payload.len());
+    frame.push((fin as u8) << 7 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(0x80 | len as u8),
+        len @ 126..=0xffff => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    let start = frame.len();
+    frame.extend_from_slice(payload);
+    apply_mask(&mut frame[start..], mask);
+    frame
+}
+
+pub(crate) fn apply_mask(data: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use vitasdk_sys::{
+        SCE_NET_ERROR_ECONNREFUSED, SCE_NET_ERROR_EINVAL, SCE_NET_ERROR_EPROTONOSUPPORT,
+    };
+
+    use super::*;
+
+    fn error(code: u32) -> SceError {
+        SceError::from_error_code(code)
+    }
+
+    /// Decodes a whole frame out of `bytes`, returning the unmasked payload.
+    fn decode(bytes: &[u8]) -> SceResult<(FrameHeader, Vec<u8>)> {
+        let mut rest = bytes;
+        let mut read_exact = |out: &mut [u8]| {
+            if rest.len() < out.len() {
+                return Err(error(SCE_NET_ERROR_EINVAL));
+            }
+            let (head, tail) = rest.split_at(out.len());
+            out.copy_from_slice(head);
+            rest = tail;
+            Ok(())
+        };
+        let header = FrameHeader::decode(&mut read_exact)?;
+        let mut payload = vec![0; header.len as usize];
+        read_exact(&mut payload)?;
+        if let Some(mask) = header.mask {
+            apply_mask(&mut payload, mask);
+        }
+        assert!(rest.is_empty());
+        Ok((header, payload))
+    }
+
+    #[test]
+    fn urls() {
+        assert_eq!(
+            parse_url("ws://example.com").unwrap(),
+            ("example.com", "example.com", 80, String::from("/"))
+        );
+        assert_eq!(
+            parse_url("ws://example.com:8080/chat?room=1#top").unwrap(),
+            (
+                "example.com:8080",
+                "example.com",
+                8080,
+                String::from("/chat?room=1")
+            )
+        );
+        assert_eq!(
+            parse_url("ws://example.com?q").unwrap().3,
+            String::from("/?q")
+        );
+        assert_eq!(
+            parse_url("wss://example.com"),
+            Err(error(SCE_NET_ERROR_EPROTONOSUPPORT))
+        );
+        for url in [
+            "http://example.com",
+            "ws://",
+            "ws://:80/",
+            "ws://example.com:port",
+        ] {
+            assert_eq!(parse_url(url), Err(error(SCE_NET_ERROR_EINVAL)), "{url}");
+        }
+    }
+
+    #[test]
+    fn urls_reject_request_injection() {
+        for url in [
+            "ws://example.com/a b",
+            "ws://example.com/a\r\nX-Injected: 1",
+            "ws://example.com/\n",
+            "ws://example.com/\t",
+            "ws://exa mple.com/",
+            "ws://example.com\r\nX-Injected: 1/",
+            "ws://example.com\x00/",
+            "ws://example.com/\x7f",
+        ] {
+            assert_eq!(parse_url(url), Err(error(SCE_NET_ERROR_EINVAL)), "{url:?}");
+        }
+        assert_eq!(
+            check_request_target("example.com", "/a\rb"),
+            Err(error(SCE_NET_ERROR_EINVAL))
+        );
+        assert_eq!(
+            check_request_target("example.com\n", "/"),
+            Err(error(SCE_NET_ERROR_EINVAL))
+        );
+        assert_eq!(check_request_target("example.com:80", "/a?b=%20"), Ok(()));
+    }
+
+    #[test]
+    fn responses() {
+        // Key and accept value of RFC 6455 section 1.3
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        let accept = "Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=";
+        let response = |status: &str, upgrade: &str, connection: &str, accept: &str| {
+            alloc::format!(
+                "{status}\r\nUpgrade: {upgrade}\r\nConnection: {connection}\r\n{accept}\r\n\r\n"
+            )
+        };
+
+        let ok = response(
+            "HTTP/1.1 101 Switching Protocols",
+            "websocket",
+            "Upgrade",
+            accept,
+        );
+        assert_eq!(check_response(ok.as_bytes(), key), Ok(()));
+        let tokens = response(
+            "HTTP/1.1 101 OK",
+            "WebSocket",
+            "keep-alive, upgrade",
+            accept,
+        );
+        assert_eq!(check_response(tokens.as_bytes(), key), Ok(()));
+
+        let refused = response("HTTP/1.1 403 Forbidden", "websocket", "Upgrade", accept);
+        assert_eq!(
+            check_response(refused.as_bytes(), key),
+            Err(error(SCE_NET_ERROR_ECONNREFUSED))
+        );
+        for bad in [
+            response("ICY 101 OK", "websocket", "Upgrade", accept),
+            response("HTTP/1.1 101 OK", "h2c", "Upgrade", accept),
+            response("HTTP/1.1 101 OK", "websocket", "keep-alive", accept),
+            response(
+                "HTTP/1.1 101 OK",
+                "websocket",
+                "Upgrade",
+                "Sec-WebSocket-Accept: x",
+            ),
+            response(
+                "HTTP/1.1 101 OK",
+                "websocket",
+                "Upgrade",
+                "X-Missing: accept",
+            ),
+        ] {
+            assert_eq!(
+                check_response(bad.as_bytes(), key),
+                Err(BAD_MESSAGE),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        for (len, header_len) in [(0, 6), (125, 6), (126, 8), (0xffff, 8), (0x10000, 14)] {
+            let payload: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let frame = encode_frame(true, OP_BINARY, &payload, mask);
+            assert_eq!(frame.len(), header_len + len);
+            let (header, decoded) = decode(&frame).unwrap();
+            assert_eq!(
+                header,
+                FrameHeader {
+                    fin: true,
+                    opcode: OP_BINARY,
+                    len: len as u64,
+                    mask: Some(mask),
+                }
+            );
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn frames_encode() {
+        // Masked "Hello" of RFC 6455 section 5.7
+        assert_eq!(
+            encode_frame(true, OP_TEXT, b"Hello", [0x37, 0xfa, 0x21, 0x3d]),
+            [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]
+        );
+        assert_eq!(
+            encode_frame(false, OP_CONTINUATION, b"", [0; 4])[..2],
+            [0x00, 0x80]
+        );
+    }
+
+    #[test]
+    fn frames_decode() {
+        // Unmasked frames of RFC 6455 section 5.7
+        let (header, payload) = decode(&[0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]).unwrap();
+        assert_eq!(
+            (header.fin, header.opcode, header.mask),
+            (true, OP_TEXT, None)
+        );
+        assert_eq!(payload, b"Hello");
+        let (header, payload) = decode(&[0x01, 0x03, 0x48, 0x65, 0x6c]).unwrap();
+        assert_eq!((header.fin, header.opcode), (false, OP_TEXT));
+        assert_eq!(payload, b"Hel");
+        let (header, _) = decode(&[0x89, 0x00]).unwrap();
+        assert_eq!(header.opcode, OP_PING);
+        let (header, _) = decode(&[0x8a, 0x00]).unwrap();
+        assert_eq!(header.opcode, OP_PONG);
+        let (header, payload) = decode(&[0x88, 0x02, 0x03, 0xe8]).unwrap();
+        assert_eq!(header.opcode, OP_CLOSE);
+        assert_eq!(payload, 1000u16.to_be_bytes());
+    }
+
+    #[test]
+    fn frames_reject_invalid_headers() {
+        // Reserved bits
+        assert_eq!(decode(&[0xc1, 0x00]), Err(BAD_MESSAGE));
+        // Fragmented control frame
+        assert_eq!(decode(&[0x09, 0x00]), Err(BAD_MESSAGE));
+        // Control frame over 125 bytes
+        let mut frame = vec![0x88, 126, 0, 126];
+        frame.resize(4 + 126, 0);
+        assert_eq!(decode(&frame), Err(BAD_MESSAGE));
+    }
+}
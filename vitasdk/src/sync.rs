@@ -0,0 +1,46 @@
+//! Kernel synchronization objects.
+//!
+//! Unlike spin locks, waiting on these puts the thread to sleep until the
+//! kernel wakes it up, so they may be used between threads of different
+//! priorities.
+
+use core::{ffi::c_uint, time::Duration};
+
+use vitasdk_sys::SCE_KERNEL_ERROR_WAIT_TIMEOUT;
+
+use crate::error::{SceError, SceResult};
+
+mod condvar;
+mod event_flag;
+#[cfg(feature = "alloc")]
+mod lw;
+mod mutex;
+pub mod pipe;
+mod semaphore;
+
+pub use condvar::Condvar;
+pub use event_flag::{EventFlag, EventWaitMode};
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use lw::{LwCond, LwMutex, LwMutexGuard, RawLwMutex};
+pub use mutex::{Mutex, MutexGuard};
+pub use semaphore::Semaphore;
+
+/// Converts `timeout` into microseconds for the kernel, saturating.
+fn raw_timeout(timeout: Duration) -> c_uint {
+    timeout.as_micros().min(c_uint::MAX as u128) as c_uint
+}
+
+/// Turns the error with `error_code` into `None`.
+fn none_on_error<T>(result: SceResult<T>, error_code: u32) -> SceResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e == SceError::from_error_code(error_code) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Turns the wait timeout error into `None`.
+fn wait_timed_out<T>(result: SceResult<T>) -> SceResult<Option<T>> {
+    none_on_error(result, SCE_KERNEL_ERROR_WAIT_TIMEOUT)
+}
@@ -0,0 +1,130 @@
+use core::{ffi::CStr, mem, ptr, time::Duration};
+
+use vitasdk_sys::{
+    sceKernelCreateCond, sceKernelDeleteCond, sceKernelSignalCond, sceKernelSignalCondAll,
+    sceKernelWaitCond,
+};
+
+use super::{raw_timeout, wait_timed_out, Mutex, MutexGuard};
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceResult},
+    types::Uid,
+};
+
+/// Kernel condition variable, bound to the [`Mutex`] it was created with.
+#[derive(Debug)]
+pub struct Condvar {
+    uid: Uid,
+    mutex: Uid,
+}
+
+impl Condvar {
+    #[doc(alias = "sceKernelCreateCond")]
+    pub fn new<T: ?Sized>(name: &CStr, mutex: &Mutex<T>) -> SceResult<Self> {
+        Ok(Condvar {
+            uid: sce_result_uid_from_code(unsafe {
+                sceKernelCreateCond(name.as_ptr(), 0, mutex.uid().get(), ptr::null())
+            })?,
+            mutex: mutex.uid(),
+        })
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Unlocks the mutex of `guard` and waits for a notification, locking it
+    /// again before returning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` belongs to another mutex than the one of the
+    /// condition variable.
+    #[doc(alias = "sceKernelWaitCond")]
+    pub fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> SceResult<MutexGuard<'a, T>> {
+        self.check_mutex(&guard);
+        sce_result_unit_from_code(unsafe { sceKernelWaitCond(self.uid.get(), ptr::null_mut()) })?;
+        Ok(guard)
+    }
+
+    /// Same as [`Condvar::wait`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelWaitCondCB")]
+    pub fn wait_cb<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> SceResult<MutexGuard<'a, T>> {
+        self.check_mutex(&guard);
+        sce_result_unit_from_code(unsafe {
+            vitasdk_sys::sceKernelWaitCondCB(self.uid.get(), ptr::null_mut())
+        })?;
+        Ok(guard)
+    }
+
+    /// Waits for a notification while `condition` returns `true`.
+    pub fn wait_while<'a, T: ?Sized>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: impl FnMut(&mut T) -> bool,
+    ) -> SceResult<MutexGuard<'a, T>> {
+        while condition(&mut guard) {
+            guard = self.wait(guard)?;
+        }
+        Ok(guard)
+    }
+
+    /// Waits up to `timeout` with microsecond precision, also returning
+    /// whether it timed out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` belongs to another mutex than the one of the
+    /// condition variable.
+    #[doc(alias = "sceKernelWaitCond")]
+    pub fn wait_timeout<'a, T: ?Sized>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: Duration,
+    ) -> SceResult<(MutexGuard<'a, T>, bool)> {
+        self.check_mutex(&guard);
+        let mut timeout = raw_timeout(timeout);
+        let res =
+            sce_result_unit_from_code(unsafe { sceKernelWaitCond(self.uid.get(), &mut timeout) });
+        let timed_out = wait_timed_out(res)?.is_none();
+        Ok((guard, timed_out))
+    }
+
+    /// Wakes up one of the waiting threads.
+    #[doc(alias = "sceKernelSignalCond")]
+    pub fn notify_one(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSignalCond(self.uid.get()) })
+    }
+
+    /// Wakes up all of the waiting threads.
+    #[doc(alias = "sceKernelSignalCondAll")]
+    pub fn notify_all(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSignalCondAll(self.uid.get()) })
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteCond(self.uid.get()) })
+    }
+
+    fn check_mutex<T: ?Sized>(&self, guard: &MutexGuard<'_, T>) {
+        assert_eq!(
+            guard.mutex().uid(),
+            self.mutex,
+            "condition variable used with another mutex"
+        );
+    }
+}
+
+impl Drop for Condvar {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
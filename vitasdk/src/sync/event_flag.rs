@@ -0,0 +1,180 @@
+use core::{
+    ffi::{c_int, CStr},
+    mem, ops, ptr,
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceKernelClearEventFlag, sceKernelCreateEventFlag, sceKernelDeleteEventFlag,
+    sceKernelGetEventFlagInfo, sceKernelPollEventFlag, sceKernelSetEventFlag,
+    sceKernelWaitEventFlag, SceKernelEventFlagInfo, SCE_EVENT_WAITAND, SCE_EVENT_WAITCLEAR,
+    SCE_EVENT_WAITCLEAR_PAT, SCE_EVENT_WAITMULTIPLE, SCE_EVENT_WAITOR, SCE_KERNEL_ERROR_EVF_COND,
+};
+
+use super::{none_on_error, raw_timeout, wait_timed_out};
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceResult},
+    types::Uid,
+};
+
+/// How [`EventFlag`] waits for its bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EventWaitMode(u32);
+
+impl EventWaitMode {
+    /// Waits for all of the bits.
+    pub const ALL: Self = EventWaitMode(SCE_EVENT_WAITAND);
+    /// Waits for any of the bits.
+    pub const ANY: Self = EventWaitMode(SCE_EVENT_WAITOR);
+    /// Clears the waited bits once the wait succeeds.
+    pub const CLEAR: Self = EventWaitMode(SCE_EVENT_WAITCLEAR);
+    /// Clears all of the bits once the wait succeeds.
+    pub const CLEAR_ALL: Self = EventWaitMode(SCE_EVENT_WAITCLEAR_PAT);
+
+    pub const fn empty() -> Self {
+        EventWaitMode(0)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for EventWaitMode {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        EventWaitMode(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for EventWaitMode {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// 32 bits threads can wait on, any number of threads at once.
+#[derive(Debug)]
+pub struct EventFlag {
+    uid: Uid,
+}
+
+impl EventFlag {
+    #[doc(alias = "sceKernelCreateEventFlag")]
+    pub fn new(name: &CStr, initial: u32) -> SceResult<Self> {
+        Ok(EventFlag {
+            uid: sce_result_uid_from_code(unsafe {
+                sceKernelCreateEventFlag(
+                    name.as_ptr(),
+                    SCE_EVENT_WAITMULTIPLE as c_int,
+                    initial as c_int,
+                    ptr::null_mut(),
+                )
+            })?,
+        })
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Sets `bits`, waking up the threads waiting for them.
+    #[doc(alias = "sceKernelSetEventFlag")]
+    pub fn set(&self, bits: u32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSetEventFlag(self.uid.get(), bits) })
+    }
+
+    /// Clears `bits`, leaving the other ones as is.
+    #[doc(alias = "sceKernelClearEventFlag")]
+    pub fn clear(&self, bits: u32) -> SceResult<()> {
+        // The kernel keeps the bits of the pattern it is given
+        sce_result_unit_from_code(unsafe { sceKernelClearEventFlag(self.uid.get(), !bits) })
+    }
+
+    /// Bits currently set.
+    #[doc(alias = "sceKernelGetEventFlagInfo")]
+    pub fn get(&self) -> SceResult<u32> {
+        let mut info: SceKernelEventFlagInfo = unsafe { mem::zeroed() };
+        info.size = mem::size_of::<SceKernelEventFlagInfo>() as _;
+        sce_result_unit_from_code(unsafe { sceKernelGetEventFlagInfo(self.uid.get(), &mut info) })?;
+        Ok(info.currentPattern)
+    }
+
+    /// Waits for `bits` according to `mode`, returning the bits set at the
+    /// time the wait succeeded.
+    #[doc(alias = "sceKernelWaitEventFlag")]
+    pub fn wait(&self, bits: u32, mode: EventWaitMode) -> SceResult<u32> {
+        let mut out = 0;
+        sce_result_unit_from_code(unsafe {
+            sceKernelWaitEventFlag(self.uid.get(), bits, mode.0, &mut out, ptr::null_mut())
+        })?;
+        Ok(out)
+    }
+
+    /// Same as [`EventFlag::wait`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelWaitEventFlagCB")]
+    pub fn wait_cb(&self, bits: u32, mode: EventWaitMode) -> SceResult<u32> {
+        let mut out = 0;
+        sce_result_unit_from_code(unsafe {
+            vitasdk_sys::sceKernelWaitEventFlagCB(
+                self.uid.get(),
+                bits,
+                mode.0,
+                &mut out,
+                ptr::null_mut(),
+            )
+        })?;
+        Ok(out)
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning `None`
+    /// if the bits weren't set by then.
+    #[doc(alias = "sceKernelWaitEventFlag")]
+    pub fn wait_timeout(
+        &self,
+        bits: u32,
+        mode: EventWaitMode,
+        timeout: Duration,
+    ) -> SceResult<Option<u32>> {
+        let mut out = 0;
+        let mut timeout = raw_timeout(timeout);
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelWaitEventFlag(self.uid.get(), bits, mode.0, &mut out, &mut timeout)
+        });
+        Ok(wait_timed_out(res)?.map(|()| out))
+    }
+
+    /// Checks for `bits` without waiting, returning `None` if they aren't
+    /// set.
+    #[doc(alias = "sceKernelPollEventFlag")]
+    pub fn poll(&self, bits: u32, mode: EventWaitMode) -> SceResult<Option<u32>> {
+        let mut out = 0;
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelPollEventFlag(self.uid.get(), bits, mode.0, &mut out)
+        });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_EVF_COND)?.map(|()| out))
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteEventFlag(self.uid.get()) })
+    }
+}
+
+impl Drop for EventFlag {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
@@ -0,0 +1,420 @@
+use alloc::boxed::Box;
+use core::{
+    cell::UnsafeCell,
+    ffi::CStr,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceKernelCreateLwCond, sceKernelCreateLwMutex, sceKernelDeleteLwCond, sceKernelDeleteLwMutex,
+    sceKernelLockLwMutex, sceKernelSignalLwCond, sceKernelSignalLwCondAll, sceKernelTryLockLwMutex,
+    sceKernelUnlockLwMutex, sceKernelWaitLwCond, SceKernelLwCondWork, SceKernelLwMutexWork,
+    SCE_KERNEL_ERROR_LW_MUTEX_FAILED_TO_OWN,
+};
+
+use super::{none_on_error, raw_timeout, wait_timed_out};
+use crate::error::{sce_result_unit_from_code, SceResult};
+
+/// Lightweight mutex without data, locked and unlocked by hand.
+///
+/// Uncontended locking happens in user space, only waiting involves the
+/// kernel. The work area of the kernel object is allocated on first use,
+/// so the mutex can be created in a `const` context and moved around.
+pub struct RawLwMutex {
+    name: &'static CStr,
+    work: AtomicPtr<SceKernelLwMutexWork>,
+}
+
+unsafe impl Send for RawLwMutex {}
+unsafe impl Sync for RawLwMutex {}
+
+impl Default for RawLwMutex {
+    fn default() -> Self {
+        RawLwMutex::new()
+    }
+}
+
+impl RawLwMutex {
+    pub const fn new() -> Self {
+        RawLwMutex {
+            name: c"RustLwMutex",
+            work: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub const fn with_name(mut self, name: &'static CStr) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Waits until the mutex is unlocked and locks it.
+    #[doc(alias = "sceKernelLockLwMutex")]
+    pub fn lock(&self) -> SceResult<()> {
+        let work = self.work()?;
+        sce_result_unit_from_code(unsafe { sceKernelLockLwMutex(work, 1, ptr::null_mut()) })
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning whether
+    /// the mutex was locked.
+    #[doc(alias = "sceKernelLockLwMutex")]
+    pub fn lock_timeout(&self, timeout: Duration) -> SceResult<bool> {
+        let work = self.work()?;
+        let mut timeout = raw_timeout(timeout);
+        let res = sce_result_unit_from_code(unsafe { sceKernelLockLwMutex(work, 1, &mut timeout) });
+        Ok(wait_timed_out(res)?.is_some())
+    }
+
+    /// Locks the mutex without waiting, returning whether it was locked.
+    #[doc(alias = "sceKernelTryLockLwMutex")]
+    pub fn try_lock(&self) -> SceResult<bool> {
+        let work = self.work()?;
+        let res = sce_result_unit_from_code(unsafe { sceKernelTryLockLwMutex(work, 1) });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_LW_MUTEX_FAILED_TO_OWN)?.is_some())
+    }
+
+    /// # Safety
+    ///
+    /// The mutex has to be locked by the calling thread.
+    #[doc(alias = "sceKernelUnlockLwMutex")]
+    pub unsafe fn unlock(&self) -> SceResult<()> {
+        let work = self.work.load(Ordering::Acquire);
+        sce_result_unit_from_code(unsafe { sceKernelUnlockLwMutex(work, 1) })
+    }
+
+    /// Returns the work area, creating the kernel object if needed.
+    #[doc(alias = "sceKernelCreateLwMutex")]
+    fn work(&self) -> SceResult<*mut SceKernelLwMutexWork> {
+        let work = self.work.load(Ordering::Acquire);
+        if !work.is_null() {
+            return Ok(work);
+        }
+        let new = Box::into_raw(Box::new(SceKernelLwMutexWork { data: [0; 4] }));
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelCreateLwMutex(new, self.name.as_ptr(), 0, 0, ptr::null())
+        });
+        if let Err(e) = res {
+            drop(unsafe { Box::from_raw(new) });
+            return Err(e);
+        }
+        match self
+            .work
+            .compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => Ok(new),
+            Err(work) => {
+                // Another thread created it first
+                unsafe { delete_work(new) };
+                Ok(work)
+            }
+        }
+    }
+}
+
+unsafe fn delete_work(work: *mut SceKernelLwMutexWork) {
+    let _ = unsafe { sceKernelDeleteLwMutex(work) };
+    drop(unsafe { Box::from_raw(work) });
+}
+
+impl Drop for RawLwMutex {
+    fn drop(&mut self) {
+        let work = *self.work.get_mut();
+        if !work.is_null() {
+            unsafe { delete_work(work) };
+        }
+    }
+}
+
+impl fmt::Debug for RawLwMutex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawLwMutex")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Lock failures are reported with a panic, which only happens on recursive
+/// locking or when the kernel runs out of objects.
+#[cfg(feature = "lock_api")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lock_api")))]
+unsafe impl lock_api::RawMutex for RawLwMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawLwMutex::new();
+
+    type GuardMarker = lock_api::GuardNoSend;
+
+    fn lock(&self) {
+        if let Err(e) = RawLwMutex::lock(self) {
+            panic!("failed to lock lightweight mutex: {e:?}");
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        RawLwMutex::try_lock(self).unwrap_or(false)
+    }
+
+    unsafe fn unlock(&self) {
+        let _ = unsafe { RawLwMutex::unlock(self) };
+    }
+}
+
+/// Lightweight mutex protecting a value.
+///
+/// The mutex isn't recursive, locking it again from the thread holding it
+/// returns an error instead of a deadlock.
+pub struct LwMutex<T: ?Sized> {
+    raw: RawLwMutex,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for LwMutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for LwMutex<T> {}
+
+impl<T> LwMutex<T> {
+    pub const fn new(value: T) -> Self {
+        LwMutex {
+            raw: RawLwMutex::new(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub const fn with_name(mut self, name: &'static CStr) -> Self {
+        self.raw.name = name;
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> LwMutex<T> {
+    /// Waits until the mutex is unlocked and locks it.
+    pub fn lock(&self) -> SceResult<LwMutexGuard<'_, T>> {
+        self.raw.lock()?;
+        Ok(LwMutexGuard::new(self))
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning `None`
+    /// if the mutex is still locked by then.
+    pub fn lock_timeout(&self, timeout: Duration) -> SceResult<Option<LwMutexGuard<'_, T>>> {
+        Ok(self
+            .raw
+            .lock_timeout(timeout)?
+            .then(|| LwMutexGuard::new(self)))
+    }
+
+    /// Locks the mutex without waiting, returning `None` if it is locked.
+    pub fn try_lock(&self) -> SceResult<Option<LwMutexGuard<'_, T>>> {
+        Ok(self.raw.try_lock()?.then(|| LwMutexGuard::new(self)))
+    }
+
+    /// Returns the value without locking, as it can't be shared.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn raw(&self) -> &RawLwMutex {
+        &self.raw
+    }
+}
+
+impl<T: Default> Default for LwMutex<T> {
+    fn default() -> Self {
+        LwMutex::new(T::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for LwMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LwMutex")
+            .field("raw", &self.raw)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Unlocks the mutex when dropped.
+///
+/// The guard can't be sent to another thread, as the kernel only lets the
+/// owner thread unlock it.
+#[must_use = "if unused the LwMutex will immediately unlock"]
+pub struct LwMutexGuard<'a, T: ?Sized> {
+    mutex: &'a LwMutex<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for LwMutexGuard<'_, T> {}
+
+impl<'a, T: ?Sized> LwMutexGuard<'a, T> {
+    fn new(mutex: &'a LwMutex<T>) -> Self {
+        LwMutexGuard {
+            mutex,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for LwMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for LwMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for LwMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let _ = unsafe { self.mutex.raw.unlock() };
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for LwMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Lightweight condition variable, bound to the mutex it was created with
+/// for its whole lifetime.
+pub struct LwCond<'m> {
+    work: Box<UnsafeCell<SceKernelLwCondWork>>,
+    mutex: &'m RawLwMutex,
+}
+
+unsafe impl Send for LwCond<'_> {}
+unsafe impl Sync for LwCond<'_> {}
+
+impl<'m> LwCond<'m> {
+    #[doc(alias = "sceKernelCreateLwCond")]
+    pub fn new<T: ?Sized>(name: &CStr, mutex: &'m LwMutex<T>) -> SceResult<Self> {
+        Self::with_raw(name, &mutex.raw)
+    }
+
+    /// Creates a condition variable for a mutex locked by hand.
+    pub fn with_raw(name: &CStr, mutex: &'m RawLwMutex) -> SceResult<Self> {
+        let work = Box::new(UnsafeCell::new(SceKernelLwCondWork { data: [0; 4] }));
+        sce_result_unit_from_code(unsafe {
+            sceKernelCreateLwCond(work.get(), name.as_ptr(), 0, mutex.work()?, ptr::null())
+        })?;
+        Ok(LwCond { work, mutex })
+    }
+
+    /// Unlocks the mutex of `guard` and waits for a notification, locking it
+    /// again before returning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` belongs to another mutex than the one of the
+    /// condition variable.
+    #[doc(alias = "sceKernelWaitLwCond")]
+    pub fn wait<'a, T: ?Sized>(
+        &self,
+        guard: LwMutexGuard<'a, T>,
+    ) -> SceResult<LwMutexGuard<'a, T>> {
+        self.check_mutex(&guard);
+        sce_result_unit_from_code(unsafe {
+            sceKernelWaitLwCond(self.work.get(), ptr::null_mut())
+        })?;
+        Ok(guard)
+    }
+
+    /// Waits for a notification while `condition` returns `true`.
+    pub fn wait_while<'a, T: ?Sized>(
+        &self,
+        mut guard: LwMutexGuard<'a, T>,
+        mut condition: impl FnMut(&mut T) -> bool,
+    ) -> SceResult<LwMutexGuard<'a, T>> {
+        while condition(&mut guard) {
+            guard = self.wait(guard)?;
+        }
+        Ok(guard)
+    }
+
+    /// Waits up to `timeout` with microsecond precision, also returning
+    /// whether it timed out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` belongs to another mutex than the one of the
+    /// condition variable.
+    #[doc(alias = "sceKernelWaitLwCond")]
+    pub fn wait_timeout<'a, T: ?Sized>(
+        &self,
+        guard: LwMutexGuard<'a, T>,
+        timeout: Duration,
+    ) -> SceResult<(LwMutexGuard<'a, T>, bool)> {
+        self.check_mutex(&guard);
+        let mut timeout = raw_timeout(timeout);
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelWaitLwCond(self.work.get(), &mut timeout)
+        });
+        let timed_out = wait_timed_out(res)?.is_none();
+        Ok((guard, timed_out))
+    }
+
+    /// Waits for a notification with the mutex locked by hand.
+    ///
+    /// # Safety
+    ///
+    /// The mutex has to be locked by the calling thread.
+    #[doc(alias = "sceKernelWaitLwCond")]
+    pub unsafe fn wait_raw(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelWaitLwCond(self.work.get(), ptr::null_mut()) })
+    }
+
+    /// Wakes up one of the waiting threads.
+    #[doc(alias = "sceKernelSignalLwCond")]
+    pub fn notify_one(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSignalLwCond(self.work.get()) })
+    }
+
+    /// Wakes up all of the waiting threads.
+    #[doc(alias = "sceKernelSignalLwCondAll")]
+    pub fn notify_all(&self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSignalLwCondAll(self.work.get()) })
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        let mut this = mem::ManuallyDrop::new(self);
+        let res = this.delete_();
+        drop(unsafe { ptr::read(&this.work) });
+        res
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteLwCond(self.work.get()) })
+    }
+
+    fn check_mutex<T: ?Sized>(&self, guard: &LwMutexGuard<'_, T>) {
+        assert!(
+            ptr::eq(&guard.mutex.raw, self.mutex),
+            "condition variable used with another mutex"
+        );
+    }
+}
+
+impl Drop for LwCond<'_> {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
+
+impl fmt::Debug for LwCond<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LwCond")
+            .field("mutex", &self.mutex)
+            .finish_non_exhaustive()
+    }
+}
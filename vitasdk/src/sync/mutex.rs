@@ -0,0 +1,180 @@
+use core::{
+    cell::UnsafeCell,
+    ffi::CStr,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr,
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceKernelCreateMutex, sceKernelDeleteMutex, sceKernelLockMutex, sceKernelTryLockMutex,
+    sceKernelUnlockMutex, SCE_KERNEL_ERROR_MUTEX_FAILED_TO_OWN,
+};
+
+use super::{none_on_error, raw_timeout, wait_timed_out};
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceResult},
+    types::Uid,
+};
+
+/// Kernel mutex protecting a value.
+///
+/// The mutex isn't recursive, locking it again from the thread holding it
+/// returns an error instead of a deadlock.
+pub struct Mutex<T: ?Sized> {
+    uid: Uid,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    #[doc(alias = "sceKernelCreateMutex")]
+    pub fn new(name: &CStr, value: T) -> SceResult<Self> {
+        Ok(Mutex {
+            uid: sce_result_uid_from_code(unsafe {
+                sceKernelCreateMutex(name.as_ptr(), 0, 0, ptr::null_mut())
+            })?,
+            value: UnsafeCell::new(value),
+        })
+    }
+
+    /// Deletes the mutex, returning the value.
+    pub fn into_inner(self) -> T {
+        let mut this = mem::ManuallyDrop::new(self);
+        let _ = this.delete_();
+        unsafe { ptr::read(this.value.get()) }
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        let mut this = mem::ManuallyDrop::new(self);
+        let res = this.delete_();
+        unsafe { ptr::drop_in_place(this.value.get()) };
+        res
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Waits until the mutex is unlocked and locks it.
+    #[doc(alias = "sceKernelLockMutex")]
+    pub fn lock(&self) -> SceResult<MutexGuard<'_, T>> {
+        sce_result_unit_from_code(unsafe {
+            sceKernelLockMutex(self.uid.get(), 1, ptr::null_mut())
+        })?;
+        Ok(MutexGuard::new(self))
+    }
+
+    /// Same as [`Mutex::lock`], but runs callbacks of the calling thread while
+    /// waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelLockMutexCB")]
+    pub fn lock_cb(&self) -> SceResult<MutexGuard<'_, T>> {
+        sce_result_unit_from_code(unsafe {
+            vitasdk_sys::sceKernelLockMutexCB(self.uid.get(), 1, ptr::null_mut())
+        })?;
+        Ok(MutexGuard::new(self))
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning `None`
+    /// if the mutex is still locked by then.
+    #[doc(alias = "sceKernelLockMutex")]
+    pub fn lock_timeout(&self, timeout: Duration) -> SceResult<Option<MutexGuard<'_, T>>> {
+        let mut timeout = raw_timeout(timeout);
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelLockMutex(self.uid.get(), 1, &mut timeout)
+        });
+        Ok(wait_timed_out(res)?.map(|()| MutexGuard::new(self)))
+    }
+
+    /// Locks the mutex without waiting, returning `None` if it is locked.
+    #[doc(alias = "sceKernelTryLockMutex")]
+    pub fn try_lock(&self) -> SceResult<Option<MutexGuard<'_, T>>> {
+        let res = sce_result_unit_from_code(unsafe { sceKernelTryLockMutex(self.uid.get(), 1) });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_MUTEX_FAILED_TO_OWN)?
+            .map(|()| MutexGuard::new(self)))
+    }
+
+    /// Returns the value without locking, as it can't be shared.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteMutex(self.uid.get()) })
+    }
+}
+
+impl<T: ?Sized> Drop for Mutex<T> {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex")
+            .field("uid", &self.uid)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Unlocks the mutex when dropped.
+///
+/// The guard can't be sent to another thread, as the kernel only lets the
+/// owner thread unlock it.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    fn new(mutex: &'a Mutex<T>) -> Self {
+        MutexGuard {
+            mutex,
+            _not_send: PhantomData,
+        }
+    }
+
+    pub(super) fn mutex(&self) -> &'a Mutex<T> {
+        self.mutex
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let _ = unsafe { sceKernelUnlockMutex(self.mutex.uid.get(), 1) };
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
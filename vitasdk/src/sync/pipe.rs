@@ -0,0 +1,331 @@
+//! Typed channels over kernel message pipes.
+//!
+//! Messages are copied into the kernel buffer of the pipe, so sending and
+//! receiving never allocates. A [`Pipe`] is shared by borrowing, so it is
+//! meant for scoped threads, or to be leaked to be used from anywhere.
+
+use core::{
+    ffi::{c_int, c_uint, c_void, CStr},
+    fmt,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr,
+    time::Duration,
+};
+
+use vitasdk_sys::{
+    sceKernelCreateMsgPipe, sceKernelDeleteMsgPipe, sceKernelGetMsgPipeInfo,
+    sceKernelReceiveMsgPipe, sceKernelSendMsgPipe, sceKernelTryReceiveMsgPipe,
+    sceKernelTrySendMsgPipe, SceKernelMppInfo, SceSize, SceUID, SCE_KERNEL_ERROR_ILLEGAL_SIZE,
+    SCE_KERNEL_ERROR_MSG_PIPE_EMPTY, SCE_KERNEL_ERROR_MSG_PIPE_FULL,
+};
+
+use super::{none_on_error, raw_timeout, wait_timed_out};
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceError, SceResult},
+    types::Uid,
+};
+
+/// Memory type of the pipe buffer, user main memory.
+const MEMORY_TYPE_USER_MAIN: c_int = 0x40;
+/// Buffer size granularity of the kernel.
+const BUFFER_ALIGN: usize = 0x1000;
+/// Waits until the whole message is transferred.
+const WAIT_MODE_FULL: c_int = 1;
+
+/// Signature shared by the send and receive functions, with or without
+/// callbacks.
+type TransferFn =
+    unsafe extern "C" fn(SceUID, *mut c_void, SceSize, c_int, *mut c_void, *mut c_uint) -> c_int;
+
+/// Kernel message pipe carrying values of `T`.
+///
+/// Every message is transferred whole, so any number of threads may send
+/// and receive at once.
+pub struct Pipe<T: Copy> {
+    uid: Uid,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for Pipe<T> {}
+unsafe impl<T: Copy + Send> Sync for Pipe<T> {}
+
+impl<T: Copy> Pipe<T> {
+    /// Creates a pipe buffering at least `capacity` messages, the buffer size
+    /// being rounded up to 4 KiB.
+    #[doc(alias = "sceKernelCreateMsgPipe")]
+    pub fn new(name: &CStr, capacity: usize) -> SceResult<Self> {
+        if mem::size_of::<T>() == 0 {
+            return Err(SceError::from_error_code(SCE_KERNEL_ERROR_ILLEGAL_SIZE));
+        }
+        let size = capacity
+            .max(1)
+            .checked_mul(mem::size_of::<T>())
+            .and_then(|size| size.checked_next_multiple_of(BUFFER_ALIGN))
+            .ok_or(SceError::from_error_code(SCE_KERNEL_ERROR_ILLEGAL_SIZE))?;
+        Ok(Pipe {
+            uid: sce_result_uid_from_code(unsafe {
+                sceKernelCreateMsgPipe(
+                    name.as_ptr(),
+                    MEMORY_TYPE_USER_MAIN,
+                    0,
+                    size as c_uint,
+                    ptr::null_mut(),
+                )
+            })?,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Returns the sending and receiving halves of the pipe.
+    pub fn split(&self) -> (Sender<'_, T>, Receiver<'_, T>) {
+        (Sender { pipe: self }, Receiver { pipe: self })
+    }
+
+    pub fn sender(&self) -> Sender<'_, T> {
+        Sender { pipe: self }
+    }
+
+    pub fn receiver(&self) -> Receiver<'_, T> {
+        Receiver { pipe: self }
+    }
+
+    /// Number of messages the buffer can hold.
+    #[doc(alias = "sceKernelGetMsgPipeInfo")]
+    pub fn capacity(&self) -> SceResult<usize> {
+        Ok(self.info()?.bufSize as usize / mem::size_of::<T>())
+    }
+
+    /// Number of messages waiting to be received.
+    #[doc(alias = "sceKernelGetMsgPipeInfo")]
+    pub fn len(&self) -> SceResult<usize> {
+        let info = self.info()?;
+        Ok((info.bufSize - info.freeSize) as usize / mem::size_of::<T>())
+    }
+
+    #[doc(alias = "sceKernelGetMsgPipeInfo")]
+    pub fn is_empty(&self) -> SceResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn info(&self) -> SceResult<SceKernelMppInfo> {
+        let mut info: SceKernelMppInfo = unsafe { mem::zeroed() };
+        info.size = mem::size_of::<SceKernelMppInfo>() as _;
+        sce_result_unit_from_code(unsafe { sceKernelGetMsgPipeInfo(self.uid.get(), &mut info) })?;
+        Ok(info)
+    }
+
+    fn send_(&self, send: TransferFn, value: T, timeout: *mut c_uint) -> SceResult<()> {
+        let mut value = MaybeUninit::new(value);
+        let mut sent: SceSize = 0;
+        sce_result_unit_from_code(unsafe {
+            send(
+                self.uid.get(),
+                value.as_mut_ptr().cast(),
+                mem::size_of::<T>() as SceSize,
+                WAIT_MODE_FULL,
+                (&mut sent as *mut SceSize).cast(),
+                timeout,
+            )
+        })
+    }
+
+    fn recv_(&self, recv: TransferFn, timeout: *mut c_uint) -> SceResult<T> {
+        let mut value = MaybeUninit::<T>::uninit();
+        let mut received: SceSize = 0;
+        sce_result_unit_from_code(unsafe {
+            recv(
+                self.uid.get(),
+                value.as_mut_ptr().cast(),
+                mem::size_of::<T>() as SceSize,
+                WAIT_MODE_FULL,
+                (&mut received as *mut SceSize).cast(),
+                timeout,
+            )
+        })?;
+        Ok(unsafe { value.assume_init() })
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteMsgPipe(self.uid.get()) })
+    }
+}
+
+impl<T: Copy> Drop for Pipe<T> {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
+
+impl<T: Copy> fmt::Debug for Pipe<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipe").field("uid", &self.uid).finish()
+    }
+}
+
+/// Sending half of a [`Pipe`].
+pub struct Sender<'a, T: Copy> {
+    pipe: &'a Pipe<T>,
+}
+
+impl<T: Copy> Sender<'_, T> {
+    /// Waits until there is room for `value` in the pipe and sends it.
+    #[doc(alias = "sceKernelSendMsgPipe")]
+    pub fn send(&self, value: T) -> SceResult<()> {
+        self.pipe
+            .send_(sceKernelSendMsgPipe, value, ptr::null_mut())
+    }
+
+    /// Same as [`Sender::send`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelSendMsgPipeCB")]
+    pub fn send_cb(&self, value: T) -> SceResult<()> {
+        self.pipe
+            .send_(vitasdk_sys::sceKernelSendMsgPipeCB, value, ptr::null_mut())
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning whether
+    /// `value` was sent.
+    #[doc(alias = "sceKernelSendMsgPipe")]
+    pub fn send_timeout(&self, value: T, timeout: Duration) -> SceResult<bool> {
+        let mut timeout = raw_timeout(timeout);
+        Ok(wait_timed_out(self.pipe.send_(sceKernelSendMsgPipe, value, &mut timeout))?.is_some())
+    }
+
+    /// Sends `value` without waiting, returning whether there was room for
+    /// it.
+    #[doc(alias = "sceKernelTrySendMsgPipe")]
+    pub fn try_send(&self, value: T) -> SceResult<bool> {
+        let mut value = MaybeUninit::new(value);
+        let mut sent: SceSize = 0;
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelTrySendMsgPipe(
+                self.pipe.uid.get(),
+                value.as_mut_ptr().cast(),
+                mem::size_of::<T>() as SceSize,
+                WAIT_MODE_FULL,
+                (&mut sent as *mut SceSize).cast(),
+            )
+        });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_MSG_PIPE_FULL)?.is_some())
+    }
+
+    pub fn pipe(&self) -> &Pipe<T> {
+        self.pipe
+    }
+}
+
+impl<T: Copy> Clone for Sender<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Copy> Copy for Sender<'_, T> {}
+
+impl<T: Copy> fmt::Debug for Sender<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").field("pipe", &self.pipe).finish()
+    }
+}
+
+/// Receiving half of a [`Pipe`].
+pub struct Receiver<'a, T: Copy> {
+    pipe: &'a Pipe<T>,
+}
+
+impl<T: Copy> Receiver<'_, T> {
+    /// Waits for a message and receives it.
+    #[doc(alias = "sceKernelReceiveMsgPipe")]
+    pub fn recv(&self) -> SceResult<T> {
+        self.pipe.recv_(sceKernelReceiveMsgPipe, ptr::null_mut())
+    }
+
+    /// Same as [`Receiver::recv`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelReceiveMsgPipeCB")]
+    pub fn recv_cb(&self) -> SceResult<T> {
+        self.pipe
+            .recv_(vitasdk_sys::sceKernelReceiveMsgPipeCB, ptr::null_mut())
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning `None`
+    /// if no message came by then.
+    #[doc(alias = "sceKernelReceiveMsgPipe")]
+    pub fn recv_timeout(&self, timeout: Duration) -> SceResult<Option<T>> {
+        let mut timeout = raw_timeout(timeout);
+        wait_timed_out(self.pipe.recv_(sceKernelReceiveMsgPipe, &mut timeout))
+    }
+
+    /// Receives a message without waiting, returning `None` if there is
+    /// none.
+    #[doc(alias = "sceKernelTryReceiveMsgPipe")]
+    pub fn try_recv(&self) -> SceResult<Option<T>> {
+        let mut value = MaybeUninit::<T>::uninit();
+        let mut received: SceSize = 0;
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelTryReceiveMsgPipe(
+                self.pipe.uid.get(),
+                value.as_mut_ptr().cast(),
+                mem::size_of::<T>() as SceSize,
+                WAIT_MODE_FULL,
+                (&mut received as *mut SceSize).cast(),
+            )
+        });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_MSG_PIPE_EMPTY)?
+            .map(|()| unsafe { value.assume_init() }))
+    }
+
+    /// Returns an iterator receiving the messages which are already there.
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { receiver: self }
+    }
+
+    pub fn pipe(&self) -> &Pipe<T> {
+        self.pipe
+    }
+}
+
+impl<T: Copy> Clone for Receiver<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Copy> Copy for Receiver<'_, T> {}
+
+impl<T: Copy> fmt::Debug for Receiver<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("pipe", &self.pipe)
+            .finish()
+    }
+}
+
+/// Iterator over the pending messages of a [`Receiver`], stopping at the
+/// first error.
+#[derive(Debug)]
+pub struct TryIter<'a, T: Copy> {
+    receiver: &'a Receiver<'a, T>,
+}
+
+impl<T: Copy> Iterator for TryIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.try_recv().ok().flatten()
+    }
+}
@@ -0,0 +1,103 @@
+use core::{ffi::CStr, mem, ptr, time::Duration};
+
+use vitasdk_sys::{
+    sceKernelCreateSema, sceKernelDeleteSema, sceKernelGetSemaInfo, sceKernelPollSema,
+    sceKernelSignalSema, sceKernelWaitSema, SceKernelSemaInfo, SCE_KERNEL_ERROR_SEMA_ZERO,
+};
+
+use super::{none_on_error, raw_timeout, wait_timed_out};
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, SceResult},
+    types::Uid,
+};
+
+/// Counting kernel semaphore.
+#[derive(Debug)]
+pub struct Semaphore {
+    uid: Uid,
+}
+
+impl Semaphore {
+    /// Creates a semaphore with `initial` resources, which can't be signaled
+    /// over `max`.
+    #[doc(alias = "sceKernelCreateSema")]
+    pub fn new(name: &CStr, initial: i32, max: i32) -> SceResult<Self> {
+        Ok(Semaphore {
+            uid: sce_result_uid_from_code(unsafe {
+                sceKernelCreateSema(name.as_ptr(), 0, initial, max, ptr::null_mut())
+            })?,
+        })
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    /// Releases `count` resources, waking up the threads waiting for them.
+    #[doc(alias = "sceKernelSignalSema")]
+    pub fn signal(&self, count: i32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelSignalSema(self.uid.get(), count) })
+    }
+
+    /// Waits until `count` resources are available and takes them.
+    #[doc(alias = "sceKernelWaitSema")]
+    pub fn wait(&self, count: i32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            sceKernelWaitSema(self.uid.get(), count, ptr::null_mut())
+        })
+    }
+
+    /// Same as [`Semaphore::wait`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelWaitSemaCB")]
+    pub fn wait_cb(&self, count: i32) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            vitasdk_sys::sceKernelWaitSemaCB(self.uid.get(), count, ptr::null_mut())
+        })
+    }
+
+    /// Waits up to `timeout` with microsecond precision, returning whether
+    /// the resources were taken.
+    #[doc(alias = "sceKernelWaitSema")]
+    pub fn wait_timeout(&self, count: i32, timeout: Duration) -> SceResult<bool> {
+        let mut timeout = raw_timeout(timeout);
+        let res = sce_result_unit_from_code(unsafe {
+            sceKernelWaitSema(self.uid.get(), count, &mut timeout)
+        });
+        Ok(wait_timed_out(res)?.is_some())
+    }
+
+    /// Takes `count` resources without waiting, returning whether there were
+    /// enough of them.
+    #[doc(alias = "sceKernelPollSema")]
+    pub fn try_wait(&self, count: i32) -> SceResult<bool> {
+        let res = sce_result_unit_from_code(unsafe { sceKernelPollSema(self.uid.get(), count) });
+        Ok(none_on_error(res, SCE_KERNEL_ERROR_SEMA_ZERO)?.is_some())
+    }
+
+    /// Number of resources currently available.
+    #[doc(alias = "sceKernelGetSemaInfo")]
+    pub fn count(&self) -> SceResult<i32> {
+        let mut info: SceKernelSemaInfo = unsafe { mem::zeroed() };
+        info.size = mem::size_of::<SceKernelSemaInfo>() as _;
+        sce_result_unit_from_code(unsafe { sceKernelGetSemaInfo(self.uid.get(), &mut info) })?;
+        Ok(info.currentCount)
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn delete(self) -> SceResult<()> {
+        mem::ManuallyDrop::new(self).delete_()
+    }
+
+    fn delete_(&mut self) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe { sceKernelDeleteSema(self.uid.get()) })
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        let _ = self.delete_();
+    }
+}
@@ -0,0 +1,576 @@
+//! Kernel threads with control over stack size, priority and CPU affinity.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    ffi::{c_int, c_void, CStr},
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops, ptr,
+    sync::atomic::{AtomicPtr, AtomicU8, Ordering},
+};
+
+use vitasdk_sys::{
+    sceKernelChangeThreadCpuAffinityMask, sceKernelChangeThreadPriority, sceKernelCreateThread,
+    sceKernelDeleteThread, sceKernelExitDeleteThread, sceKernelGetThreadCurrentPriority,
+    sceKernelGetThreadId, sceKernelStartThread, sceKernelWaitThreadEnd, SceSize,
+    SCE_KERNEL_CPU_MASK_SYSTEM, SCE_KERNEL_CPU_MASK_USER_0, SCE_KERNEL_CPU_MASK_USER_1,
+    SCE_KERNEL_CPU_MASK_USER_2, SCE_KERNEL_CPU_MASK_USER_ALL,
+    SCE_KERNEL_THREAD_CPU_AFFINITY_MASK_DEFAULT,
+};
+
+use crate::{
+    error::{sce_result_uid_from_code, sce_result_unit_from_code, sce_result_usize_from_code},
+    types::Uid,
+    SceResult,
+};
+
+pub use crate::time::sleep;
+
+/// Priority of the threads created by default, lower values run first.
+pub const DEFAULT_PRIORITY: i32 = 0x10000100;
+/// Highest priority of user threads.
+pub const HIGHEST_PRIORITY: i32 = 64;
+/// Lowest priority of user threads.
+pub const LOWEST_PRIORITY: i32 = 191;
+
+/// Spawns a thread with the default [`Builder`] settings.
+pub fn spawn<F, T>(f: F) -> SceResult<JoinHandle<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Builder::new().spawn(f)
+}
+
+/// Returns uid of the calling thread.
+#[doc(alias = "sceKernelGetThreadId")]
+pub fn current_uid() -> Uid {
+    Uid::new(unsafe { sceKernelGetThreadId() }).expect("thread id is zero")
+}
+
+#[doc(alias = "sceKernelGetThreadCurrentPriority")]
+pub fn current_priority() -> SceResult<i32> {
+    sce_result_usize_from_code(unsafe { sceKernelGetThreadCurrentPriority() })
+        .map(|priority| priority as i32)
+}
+
+/// Set of CPU cores a thread may run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuAffinity(u32);
+
+impl CpuAffinity {
+    /// Inherits affinity of the creating thread.
+    pub const DEFAULT: Self = CpuAffinity(SCE_KERNEL_THREAD_CPU_AFFINITY_MASK_DEFAULT);
+    pub const USER_0: Self = CpuAffinity(SCE_KERNEL_CPU_MASK_USER_0);
+    pub const USER_1: Self = CpuAffinity(SCE_KERNEL_CPU_MASK_USER_1);
+    pub const USER_2: Self = CpuAffinity(SCE_KERNEL_CPU_MASK_USER_2);
+    pub const USER_ALL: Self = CpuAffinity(SCE_KERNEL_CPU_MASK_USER_ALL);
+    pub const SYSTEM: Self = CpuAffinity(SCE_KERNEL_CPU_MASK_SYSTEM);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for CpuAffinity {
+    fn default() -> Self {
+        CpuAffinity::DEFAULT
+    }
+}
+
+impl ops::BitOr for CpuAffinity {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        CpuAffinity(self.0 | rhs.0)
+    }
+}
+
+/// Thread configuration.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Builder<'a> {
+    name: &'a CStr,
+    stack_size: usize,
+    priority: i32,
+    cpu_affinity: CpuAffinity,
+}
+
+impl Default for Builder<'_> {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl<'a> Builder<'a> {
+    pub const DEFAULT_STACK_SIZE: usize = 0x10000;
+
+    pub fn new() -> Self {
+        Builder {
+            name: c"RustThread",
+            stack_size: Self::DEFAULT_STACK_SIZE,
+            priority: DEFAULT_PRIORITY,
+            cpu_affinity: CpuAffinity::DEFAULT,
+        }
+    }
+
+    pub fn with_name(mut self, name: &'a CStr) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Sets stack size, which is rounded up to 4 KiB by the kernel.
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    /// Sets priority from [`HIGHEST_PRIORITY`] to [`LOWEST_PRIORITY`], or
+    /// relative to [`DEFAULT_PRIORITY`].
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_cpu_affinity(mut self, cpu_affinity: CpuAffinity) -> Self {
+        self.cpu_affinity = cpu_affinity;
+        self
+    }
+
+    #[doc(alias = "sceKernelCreateThread")]
+    #[doc(alias = "sceKernelStartThread")]
+    pub fn spawn<F, T>(self, f: F) -> SceResult<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (uid, packet) = unsafe { self.spawn_unchecked(f)? };
+        Ok(JoinHandle { uid, packet })
+    }
+
+    /// Spawns a thread which may borrow from outside of `scope`.
+    pub fn spawn_scoped<'scope, 'env, F, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> SceResult<ScopedJoinHandle<'scope, T>>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let (uid, packet) = unsafe { self.spawn_unchecked(f)? };
+        scope.threads.push(uid);
+        Ok(ScopedJoinHandle {
+            uid,
+            packet,
+            _scope: PhantomData,
+        })
+    }
+
+    /// # Safety
+    ///
+    /// Thread must be waited for before anything borrowed by `f` goes away.
+    unsafe fn spawn_unchecked<'f, F, T>(self, f: F) -> SceResult<(Uid, Arc<Packet<T>>)>
+    where
+        F: FnOnce() -> T + Send + 'f,
+        T: Send + 'f,
+    {
+        let packet = Arc::new(Packet {
+            state: AtomicU8::new(RUNNING),
+            outcome: UnsafeCell::new(None),
+        });
+        let main: MainFn<'f> = Box::new({
+            let packet = Arc::clone(&packet);
+            move || {
+                let outcome = run(f);
+                unsafe { *packet.outcome.get() = Some(outcome) };
+                packet
+                    .state
+                    .compare_exchange(RUNNING, FINISHED, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+            }
+        });
+        let main = unsafe { mem::transmute::<MainFn<'f>, MainFn<'static>>(main) };
+
+        let uid = sce_result_uid_from_code(unsafe {
+            sceKernelCreateThread(
+                self.name.as_ptr(),
+                Some(entry),
+                self.priority,
+                self.stack_size as SceSize,
+                0,
+                self.cpu_affinity.0 as c_int,
+                ptr::null(),
+            )
+        })?;
+        let main = Box::into_raw(Box::new(main));
+        let started = sce_result_unit_from_code(unsafe {
+            sceKernelStartThread(
+                uid.get(),
+                mem::size_of::<*mut MainFn>() as SceSize,
+                (&main as *const *mut MainFn).cast_mut().cast(),
+            )
+        });
+        if let Err(e) = started {
+            drop(unsafe { Box::from_raw(main) });
+            let _ = unsafe { sceKernelDeleteThread(uid.get()) };
+            return Err(e);
+        }
+        Ok((uid, packet))
+    }
+}
+
+type MainFn<'a> = Box<dyn FnOnce() -> bool + Send + 'a>;
+
+const RUNNING: u8 = 0;
+const FINISHED: u8 = 1;
+const DETACHED: u8 = 2;
+
+unsafe extern "C" fn entry(_args: SceSize, argp: *mut c_void) -> c_int {
+    let main = unsafe { Box::from_raw(argp.cast::<*mut MainFn>().read_unaligned()) };
+    if main() {
+        // Nobody is going to join the thread
+        unsafe { sceKernelExitDeleteThread(0) };
+    }
+    0
+}
+
+struct Packet<T> {
+    state: AtomicU8,
+    outcome: UnsafeCell<Option<Outcome<T>>>,
+}
+
+unsafe impl<T: Send> Sync for Packet<T> {}
+
+impl<T> Packet<T> {
+    /// # Safety
+    ///
+    /// Thread has to be finished.
+    unsafe fn take(&self) -> T {
+        let outcome = unsafe { (*self.outcome.get()).take() };
+        unwrap_outcome(outcome.expect("thread ended without running to completion"))
+    }
+}
+
+#[cfg(feature = "std")]
+type Outcome<T> = std::thread::Result<T>;
+#[cfg(not(feature = "std"))]
+type Outcome<T> = T;
+
+#[cfg(feature = "std")]
+fn run<T>(f: impl FnOnce() -> T) -> Outcome<T> {
+    std::panic::catch_unwind(core::panic::AssertUnwindSafe(f))
+}
+
+#[cfg(not(feature = "std"))]
+fn run<T>(f: impl FnOnce() -> T) -> Outcome<T> {
+    f()
+}
+
+#[cfg(feature = "std")]
+fn unwrap_outcome<T>(outcome: Outcome<T>) -> T {
+    outcome.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+#[cfg(not(feature = "std"))]
+fn unwrap_outcome<T>(outcome: Outcome<T>) -> T {
+    outcome
+}
+
+fn wait_thread_end(uid: Uid) -> SceResult<()> {
+    sce_result_usize_from_code(unsafe {
+        sceKernelWaitThreadEnd(uid.get(), ptr::null_mut(), ptr::null_mut())
+    })
+    .map(drop)
+}
+
+#[cfg(feature = "callback")]
+fn wait_thread_end_cb(uid: Uid) -> SceResult<()> {
+    sce_result_usize_from_code(unsafe {
+        vitasdk_sys::sceKernelWaitThreadEndCB(uid.get(), ptr::null_mut(), ptr::null_mut())
+    })
+    .map(drop)
+}
+
+#[doc(alias = "sceKernelChangeThreadPriority")]
+fn set_priority(uid: Uid, priority: i32) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe { sceKernelChangeThreadPriority(uid.get(), priority) })
+}
+
+fn set_cpu_affinity(uid: Uid, cpu_affinity: CpuAffinity) -> SceResult<()> {
+    sce_result_unit_from_code(unsafe {
+        sceKernelChangeThreadCpuAffinityMask(uid.get(), cpu_affinity.0 as c_int)
+    })
+}
+
+/// Owned permission to join a thread, which is detached on drop.
+pub struct JoinHandle<T> {
+    uid: Uid,
+    packet: Arc<Packet<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.packet.state.load(Ordering::Acquire) == FINISHED
+    }
+
+    #[doc(alias = "sceKernelChangeThreadPriority")]
+    pub fn set_priority(&self, priority: i32) -> SceResult<()> {
+        set_priority(self.uid, priority)
+    }
+
+    #[doc(alias = "sceKernelChangeThreadCpuAffinityMask")]
+    pub fn set_cpu_affinity(&self, cpu_affinity: CpuAffinity) -> SceResult<()> {
+        set_cpu_affinity(self.uid, cpu_affinity)
+    }
+
+    /// Waits for the thread to finish and returns the value of its closure.
+    ///
+    /// With the `std` feature, a panic of the thread is resumed here.
+    #[doc(alias = "sceKernelWaitThreadEnd")]
+    #[doc(alias = "sceKernelDeleteThread")]
+    pub fn join(self) -> SceResult<T> {
+        self.join_(wait_thread_end)
+    }
+
+    /// Same as [`JoinHandle::join`], but runs callbacks of the calling thread
+    /// while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelWaitThreadEndCB")]
+    pub fn join_cb(self) -> SceResult<T> {
+        self.join_(wait_thread_end_cb)
+    }
+
+    fn join_(self, wait: fn(Uid) -> SceResult<()>) -> SceResult<T> {
+        wait(self.uid)?;
+        let this = ManuallyDrop::new(self);
+        let packet = unsafe { ptr::read(&this.packet) };
+        let _ = unsafe { sceKernelDeleteThread(this.uid.get()) };
+        Ok(unsafe { packet.take() })
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        let detached = self.packet.state.compare_exchange(
+            RUNNING,
+            DETACHED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        if detached.is_err() {
+            // Thread is done running the closure, and won't delete itself
+            let _ = wait_thread_end(self.uid);
+            let _ = unsafe { sceKernelDeleteThread(self.uid.get()) };
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("uid", &self.uid)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a scope in which threads may borrow non-`'static` data, all of
+/// them being joined before it returns.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        threads: ThreadList::new(),
+        _scope: PhantomData,
+        _env: PhantomData,
+    };
+    let outcome = run(|| f(&scope));
+    scope.join_all();
+    unwrap_outcome(outcome)
+}
+
+/// Scope to spawn threads in, see [`scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    threads: ThreadList,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawns a thread with the default [`Builder`] settings.
+    pub fn spawn<F, T>(&'scope self, f: F) -> SceResult<ScopedJoinHandle<'scope, T>>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        Builder::new().spawn_scoped(self, f)
+    }
+
+    fn join_all(&self) {
+        // Threads may spawn more threads until they are all done, and are
+        // only deleted then as handles of other threads may still wait on them
+        let mut joined = Vec::new();
+        while let Some(uid) = self.threads.pop() {
+            wait_scoped_thread_end(uid);
+            joined.push(uid);
+        }
+        for uid in joined {
+            let _ = unsafe { sceKernelDeleteThread(uid.get()) };
+        }
+    }
+}
+
+impl core::fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+/// Permission to join a scoped thread, which is joined at the end of the
+/// scope otherwise.
+pub struct ScopedJoinHandle<'scope, T> {
+    uid: Uid,
+    packet: Arc<Packet<T>>,
+    _scope: PhantomData<&'scope ()>,
+}
+
+impl<T> ScopedJoinHandle<'_, T> {
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.packet.state.load(Ordering::Acquire) == FINISHED
+    }
+
+    #[doc(alias = "sceKernelChangeThreadPriority")]
+    pub fn set_priority(&self, priority: i32) -> SceResult<()> {
+        set_priority(self.uid, priority)
+    }
+
+    #[doc(alias = "sceKernelChangeThreadCpuAffinityMask")]
+    pub fn set_cpu_affinity(&self, cpu_affinity: CpuAffinity) -> SceResult<()> {
+        set_cpu_affinity(self.uid, cpu_affinity)
+    }
+
+    /// Waits for the thread to finish and returns the value of its closure.
+    ///
+    /// With the `std` feature, a panic of the thread is resumed here.
+    #[doc(alias = "sceKernelWaitThreadEnd")]
+    pub fn join(self) -> SceResult<T> {
+        // Thread is deleted by the scope
+        wait_thread_end(self.uid)?;
+        Ok(unsafe { self.packet.take() })
+    }
+
+    /// Same as [`ScopedJoinHandle::join`], but runs callbacks of the calling
+    /// thread while waiting.
+    #[cfg(feature = "callback")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "callback")))]
+    #[doc(alias = "sceKernelWaitThreadEndCB")]
+    pub fn join_cb(self) -> SceResult<T> {
+        wait_thread_end_cb(self.uid)?;
+        Ok(unsafe { self.packet.take() })
+    }
+}
+
+impl<T> core::fmt::Debug for ScopedJoinHandle<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScopedJoinHandle")
+            .field("uid", &self.uid)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Waits for a thread borrowing from a [`Scope`] to end.
+///
+/// Returning before the thread is done would let it use data which is gone,
+/// so the wait is retried and the process exits if it keeps failing.
+fn wait_scoped_thread_end(uid: Uid) {
+    const ATTEMPTS: usize = 4;
+    if (0..ATTEMPTS).any(|_| wait_thread_end(uid).is_ok()) {
+        return;
+    }
+    #[cfg(feature = "std")]
+    std::process::abort();
+    #[cfg(not(feature = "std"))]
+    loop {
+        let _ = unsafe { vitasdk_sys::sceKernelExitProcess(1) };
+    }
+}
+
+/// Threads of a [`Scope`], pushed without locking as a spinning thread could
+/// keep a preempted lower priority owner from ever releasing a lock.
+struct ThreadList {
+    head: AtomicPtr<ThreadNode>,
+}
+
+struct ThreadNode {
+    uid: Uid,
+    next: *mut ThreadNode,
+}
+
+impl ThreadList {
+    const fn new() -> Self {
+        ThreadList {
+            head: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    fn push(&self, uid: Uid) {
+        let node = Box::into_raw(Box::new(ThreadNode {
+            uid,
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // A failed exchange means another push succeeded
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Removes the last pushed thread.
+    ///
+    /// Only called by the thread owning the scope, so a node can't be popped
+    /// concurrently, but it can still be pushed onto.
+    fn pop(&self) -> Option<Uid> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            let next = unsafe { (*head).next };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire)
+            {
+                Ok(_) => return Some(unsafe { Box::from_raw(head) }.uid),
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl Drop for ThreadList {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
@@ -0,0 +1,284 @@
+//! Monotonic time, sleeping and frame timing.
+//!
+//! Kernel timer objects (`sceKernelCreateTimer`) aren't exposed by
+//! `vitasdk-sys`, [`Interval`] covers periodic wake ups by sleeping until
+//! each deadline instead.
+
+use core::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    time::Duration,
+};
+
+use vitasdk_sys::{sceKernelDelayThread, sceKernelGetProcessTimeWide};
+
+/// Suspends the calling thread with microsecond precision.
+#[doc(alias = "sceKernelDelayThread")]
+pub fn sleep(duration: Duration) {
+    let mut usec = duration.as_micros();
+    while usec > 0 {
+        let delay = usec.min(u32::MAX as u128) as u32;
+        let _ = unsafe { sceKernelDelayThread(delay) };
+        usec -= delay as u128;
+    }
+}
+
+/// Suspends the calling thread until `deadline`, returning right away if
+/// it already passed.
+pub fn sleep_until(deadline: Instant) {
+    sleep(deadline.saturating_duration_since(Instant::now()));
+}
+
+/// Point in the process time, which counts microseconds since the process
+/// started and doesn't advance while the system is suspended.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(u64);
+
+impl Instant {
+    #[doc(alias = "sceKernelGetProcessTimeWide")]
+    pub fn now() -> Self {
+        Instant(unsafe { sceKernelGetProcessTimeWide() })
+    }
+
+    /// Returns the process time in microseconds.
+    pub fn as_micros(&self) -> u64 {
+        self.0
+    }
+
+    /// Time elapsed since `earlier`, or `None` if it is later than `self`.
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(Duration::from_micros)
+    }
+
+    /// Time elapsed since `earlier`, or zero if it is later than `self`.
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Same as [`Instant::saturating_duration_since`].
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().saturating_duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        u64::try_from(duration.as_micros())
+            .ok()
+            .and_then(|micros| self.0.checked_add(micros))
+            .map(Instant)
+    }
+
+    /// Same as [`Instant::checked_add`], but clamps to the latest instant,
+    /// which is used for deadlines that are never reached.
+    #[cfg(feature = "async")]
+    pub(crate) fn saturating_add(&self, duration: Duration) -> Instant {
+        self.checked_add(duration).unwrap_or(Instant(u64::MAX))
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        u64::try_from(duration.as_micros())
+            .ok()
+            .and_then(|micros| self.0.checked_sub(micros))
+            .map(Instant)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see [`Instant::checked_add`].
+    fn add(self, rhs: Duration) -> Instant {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see [`Instant::checked_sub`].
+    fn sub(self, rhs: Duration) -> Instant {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
+
+impl fmt::Debug for Instant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&Duration::from_micros(self.0), f)
+    }
+}
+
+/// Wakes up periodically, every deadline following the previous one so
+/// the period doesn't drift.
+#[derive(Debug, Clone)]
+pub struct Interval {
+    period: Duration,
+    next: Instant,
+}
+
+impl Interval {
+    /// Creates an interval whose first tick completes right away.
+    pub fn new(period: Duration) -> Self {
+        Interval {
+            period,
+            next: Instant::now(),
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Sleeps until the next deadline, returning it.
+    ///
+    /// Deadlines missed by more than a period are skipped rather than
+    /// completed in a burst.
+    pub fn tick(&mut self) -> Instant {
+        sleep_until(self.next);
+        let deadline = self.next;
+        self.next += self.period;
+        let now = Instant::now();
+        if self.next < now {
+            self.next = now;
+        }
+        deadline
+    }
+
+    /// Restarts the interval, the next tick completing right away.
+    pub fn reset(&mut self) {
+        self.next = Instant::now();
+    }
+}
+
+/// Measures frame times of a game loop, [`FrameTimer::tick`] being called
+/// once per frame.
+#[derive(Debug, Clone)]
+pub struct FrameTimer {
+    start: Instant,
+    last: Instant,
+    delta: Duration,
+    max_delta: Option<Duration>,
+    frame_duration: Option<Duration>,
+    frames: u64,
+    window_start: Instant,
+    window_frames: u32,
+    fps: f32,
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        FrameTimer::new()
+    }
+}
+
+impl FrameTimer {
+    /// How often [`FrameTimer::fps`] is updated.
+    pub const FPS_WINDOW: Duration = Duration::from_millis(500);
+
+    pub fn new() -> Self {
+        let now = Instant::now();
+        FrameTimer {
+            start: now,
+            last: now,
+            delta: Duration::ZERO,
+            max_delta: None,
+            frame_duration: None,
+            frames: 0,
+            window_start: now,
+            window_frames: 0,
+            fps: 0.0,
+        }
+    }
+
+    /// Clamps the delta time, so a long stall such as loading doesn't make
+    /// the simulation jump.
+    pub fn with_max_delta(mut self, max_delta: Duration) -> Self {
+        self.max_delta = Some(max_delta);
+        self
+    }
+
+    /// Makes [`FrameTimer::tick`] sleep so frames last at least
+    /// `1 / fps` seconds, zero disabling the limit.
+    pub fn with_target_fps(mut self, fps: u32) -> Self {
+        self.frame_duration = (fps != 0).then(|| Duration::from_secs(1) / fps);
+        self
+    }
+
+    /// Ends the current frame, returning its delta time.
+    pub fn tick(&mut self) -> Duration {
+        if let Some(frame_duration) = self.frame_duration {
+            sleep_until(self.last + frame_duration);
+        }
+        let now = Instant::now();
+        let delta = now - self.last;
+        self.last = now;
+        self.delta = match self.max_delta {
+            Some(max_delta) => delta.min(max_delta),
+            None => delta,
+        };
+        self.frames += 1;
+
+        self.window_frames += 1;
+        let window = now - self.window_start;
+        if window >= Self::FPS_WINDOW {
+            self.fps = self.window_frames as f32 / window.as_secs_f32();
+            self.window_start = now;
+            self.window_frames = 0;
+        }
+        self.delta
+    }
+
+    /// Delta time of the last frame.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Delta time of the last frame in seconds.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Frames per second averaged over the last [`FrameTimer::FPS_WINDOW`],
+    /// zero until the first one passed.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Number of frames since the timer was created.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Time since the timer was created.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
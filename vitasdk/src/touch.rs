@@ -0,0 +1,54 @@
+use vitasdk_sys::SCE_TOUCH_MAX_REPORT;
+
+use crate::math::sqrt;
+
+mod gesture;
+#[cfg(feature = "touch")]
+mod panel;
+
+pub use gesture::{Gesture, GestureRecognizer};
+#[cfg(feature = "touch")]
+pub use panel::{Panel, TouchPanel, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Position in screen space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn distance(self, other: Point) -> f32 {
+        let (dx, dy) = (self.x - other.x, self.y - other.y);
+        sqrt(dx * dx + dy * dy)
+    }
+}
+
+/// Reports of all of the touches sampled at once.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchFrame {
+    /// Sampling time in microseconds.
+    pub timestamp: u64,
+    reports: [TouchReport; SCE_TOUCH_MAX_REPORT as usize],
+    len: usize,
+}
+
+impl TouchFrame {
+    pub fn reports(&self) -> &[TouchReport] {
+        &self.reports[..self.len]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[non_exhaustive]
+pub struct TouchReport {
+    /// Identifies a touch for as long as it stays on the panel.
+    pub id: u8,
+    /// Position in screen space.
+    pub position: Point,
+    /// Force ranging from 0.0 to 1.0.
+    pub force: f32,
+    /// Position in panel coordinates.
+    pub raw_x: i16,
+    pub raw_y: i16,
+}
@@ -0,0 +1,245 @@
+use core::time::Duration;
+
+use super::{Point, TouchFrame};
+
+/// Gesture recognized by [`GestureRecognizer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Short touch which didn't move, reported once it's released.
+    Tap(Point),
+    /// Single touch moving, reported on each frame it moved.
+    Drag {
+        start: Point,
+        position: Point,
+        delta: Point,
+    },
+    /// Two touches moving, `scale` is the ratio of their current distance to
+    /// the one when the second touch began.
+    Pinch { center: Point, scale: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Tracking {
+    None,
+    Single {
+        id: u8,
+        start: Point,
+        last: Point,
+        start_time: u64,
+        dragging: bool,
+        // Releasing the last touch of a pinch is not a tap
+        tap_allowed: bool,
+    },
+    Pinch {
+        ids: [u8; 2],
+        start_distance: f32,
+    },
+}
+
+/// Recognizes taps, drags and pinches out of consecutive [`TouchFrame`]s of
+/// a panel.
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    tap_timeout: u64,
+    drag_threshold: f32,
+    tracking: Tracking,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer::new()
+    }
+}
+
+impl GestureRecognizer {
+    pub const DEFAULT_TAP_TIMEOUT: Duration = Duration::from_millis(300);
+    pub const DEFAULT_DRAG_THRESHOLD: f32 = 10.0;
+
+    pub fn new() -> Self {
+        GestureRecognizer {
+            tap_timeout: Self::DEFAULT_TAP_TIMEOUT.as_micros() as u64,
+            drag_threshold: Self::DEFAULT_DRAG_THRESHOLD,
+            tracking: Tracking::None,
+        }
+    }
+
+    /// Sets the longest touch still considered a tap.
+    pub fn with_tap_timeout(mut self, timeout: Duration) -> Self {
+        self.tap_timeout = timeout.as_micros().min(u64::MAX as u128) as u64;
+        self
+    }
+
+    /// Sets the distance in screen space pixels a touch has to move before
+    /// it's considered a drag.
+    pub fn with_drag_threshold(mut self, threshold: f32) -> Self {
+        self.drag_threshold = threshold;
+        self
+    }
+
+    /// Feeds the next frame of the panel, returning the gesture it completes
+    /// or continues.
+    pub fn update(&mut self, frame: &TouchFrame) -> Option<Gesture> {
+        match *frame.reports() {
+            [] => {
+                let tracking = core::mem::replace(&mut self.tracking, Tracking::None);
+                match tracking {
+                    Tracking::Single {
+                        last,
+                        start_time,
+                        dragging: false,
+                        tap_allowed: true,
+                        ..
+                    } if frame.timestamp.saturating_sub(start_time) <= self.tap_timeout => {
+                        Some(Gesture::Tap(last))
+                    }
+                    _ => None,
+                }
+            }
+            [touch] => match &mut self.tracking {
+                Tracking::Single {
+                    id,
+                    start,
+                    last,
+                    dragging,
+                    ..
+                } if *id == touch.id => {
+                    let position = touch.position;
+                    if !*dragging && start.distance(position) > self.drag_threshold {
+                        *dragging = true;
+                    }
+                    let delta = Point {
+                        x: position.x - last.x,
+                        y: position.y - last.y,
+                    };
+                    *last = position;
+                    match *dragging && delta != Point::default() {
+                        true => Some(Gesture::Drag {
+                            start: *start,
+                            position,
+                            delta,
+                        }),
+                        false => None,
+                    }
+                }
+                tracking => {
+                    *tracking = Tracking::Single {
+                        id: touch.id,
+                        start: touch.position,
+                        last: touch.position,
+                        start_time: frame.timestamp,
+                        dragging: false,
+                        tap_allowed: matches!(tracking, Tracking::None),
+                    };
+                    None
+                }
+            },
+            [a, b, ..] => {
+                let distance = a.position.distance(b.position);
+                match self.tracking {
+                    Tracking::Pinch {
+                        ids,
+                        start_distance,
+                    } if (ids == [a.id, b.id] || ids == [b.id, a.id]) && start_distance > 0.0 => {
+                        Some(Gesture::Pinch {
+                            center: Point {
+                                x: (a.position.x + b.position.x) / 2.0,
+                                y: (a.position.y + b.position.y) / 2.0,
+                            },
+                            scale: distance / start_distance,
+                        })
+                    }
+                    _ => {
+                        self.tracking = Tracking::Pinch {
+                            ids: [a.id, b.id],
+                            start_distance: distance,
+                        };
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forgets the touches being tracked.
+    pub fn reset(&mut self) {
+        self.tracking = Tracking::None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gesture, GestureRecognizer};
+    use crate::touch::{Point, TouchFrame, TouchReport};
+
+    fn frame(timestamp: u64, touches: &[(u8, f32, f32)]) -> TouchFrame {
+        let mut frame = TouchFrame {
+            timestamp,
+            reports: Default::default(),
+            len: touches.len(),
+        };
+        for (report, &(id, x, y)) in frame.reports.iter_mut().zip(touches) {
+            *report = TouchReport {
+                id,
+                position: Point { x, y },
+                ..TouchReport::default()
+            };
+        }
+        frame
+    }
+
+    #[test]
+    fn tap() {
+        let mut recognizer = GestureRecognizer::new();
+        assert_eq!(recognizer.update(&frame(0, &[(1, 10.0, 20.0)])), None);
+        assert_eq!(recognizer.update(&frame(50_000, &[(1, 12.0, 20.0)])), None);
+        assert_eq!(
+            recognizer.update(&frame(100_000, &[])),
+            Some(Gesture::Tap(Point { x: 12.0, y: 20.0 }))
+        );
+
+        // Held longer than the tap timeout
+        assert_eq!(recognizer.update(&frame(200_000, &[(2, 10.0, 20.0)])), None);
+        assert_eq!(recognizer.update(&frame(600_000, &[])), None);
+    }
+
+    #[test]
+    fn drag() {
+        let mut recognizer = GestureRecognizer::new();
+        let start = Point { x: 100.0, y: 100.0 };
+        assert_eq!(recognizer.update(&frame(0, &[(1, 100.0, 100.0)])), None);
+        // Within the drag threshold
+        assert_eq!(recognizer.update(&frame(1, &[(1, 105.0, 100.0)])), None);
+        assert_eq!(
+            recognizer.update(&frame(2, &[(1, 130.0, 90.0)])),
+            Some(Gesture::Drag {
+                start,
+                position: Point { x: 130.0, y: 90.0 },
+                delta: Point { x: 25.0, y: -10.0 },
+            })
+        );
+        // Not moving doesn't report a drag
+        assert_eq!(recognizer.update(&frame(3, &[(1, 130.0, 90.0)])), None);
+        assert_eq!(recognizer.update(&frame(4, &[])), None);
+    }
+
+    #[test]
+    fn pinch() {
+        let mut recognizer = GestureRecognizer::new();
+        assert_eq!(recognizer.update(&frame(0, &[(1, 100.0, 100.0)])), None);
+        assert_eq!(
+            recognizer.update(&frame(1, &[(1, 100.0, 100.0), (2, 200.0, 100.0)])),
+            None
+        );
+        // Reports may list the touches in any order
+        assert_eq!(
+            recognizer.update(&frame(2, &[(2, 250.0, 100.0), (1, 50.0, 100.0)])),
+            Some(Gesture::Pinch {
+                center: Point { x: 150.0, y: 100.0 },
+                scale: 2.0,
+            })
+        );
+        // Releasing the last touch of a pinch is not a tap
+        assert_eq!(recognizer.update(&frame(3, &[(1, 50.0, 100.0)])), None);
+        assert_eq!(recognizer.update(&frame(4, &[])), None);
+    }
+}
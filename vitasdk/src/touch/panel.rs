@@ -0,0 +1,166 @@
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use vitasdk_sys::{
+    sceTouchDisableTouchForce, sceTouchEnableTouchForce, sceTouchGetPanelInfo, sceTouchPeek,
+    sceTouchRead, sceTouchSetSamplingState, SceTouchData, SceTouchPanelInfo, SceTouchPortType,
+    SceTouchReport, SCE_TOUCH_MAX_REPORT, SCE_TOUCH_PORT_BACK, SCE_TOUCH_PORT_FRONT,
+    SCE_TOUCH_SAMPLING_STATE_START, SCE_TOUCH_SAMPLING_STATE_STOP,
+};
+
+use super::{Point, TouchFrame, TouchReport};
+use crate::{
+    display::FramebufDesc,
+    error::{sce_result_unit_from_code, sce_result_usize_from_code, SceResult},
+};
+
+/// Width of the screen space touch coordinates are converted to, which is
+/// the width of the native framebuffer.
+pub const SCREEN_WIDTH: f32 = FramebufDesc::NATIVE.width as f32;
+/// Height of the screen space touch coordinates are converted to, which is
+/// the height of the native framebuffer.
+pub const SCREEN_HEIGHT: f32 = FramebufDesc::NATIVE.height as f32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Panel {
+    Front,
+    Back,
+}
+
+impl Panel {
+    fn to_raw(self) -> SceTouchPortType {
+        match self {
+            Panel::Front => SCE_TOUCH_PORT_FRONT,
+            Panel::Back => SCE_TOUCH_PORT_BACK,
+        }
+    }
+
+    fn users(self) -> &'static AtomicU32 {
+        static USERS: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+        &USERS[self.to_raw() as usize]
+    }
+}
+
+/// Touch panel sampling reports.
+///
+/// Sampling starts when the first instance of a panel is opened, and stops
+/// once all of them are dropped.
+#[derive(Debug)]
+pub struct TouchPanel {
+    panel: Panel,
+    info: SceTouchPanelInfo,
+}
+
+impl TouchPanel {
+    #[doc(alias = "sceTouchSetSamplingState")]
+    #[doc(alias = "sceTouchGetPanelInfo")]
+    pub fn open(panel: Panel) -> SceResult<Self> {
+        let mut info = MaybeUninit::<SceTouchPanelInfo>::uninit();
+        sce_result_unit_from_code(unsafe {
+            sceTouchGetPanelInfo(panel.to_raw(), info.as_mut_ptr())
+        })?;
+        let info = unsafe { info.assume_init() };
+        if panel.users().fetch_add(1, Ordering::AcqRel) == 0 {
+            if let Err(e) = set_sampling(panel, true) {
+                panel.users().fetch_sub(1, Ordering::AcqRel);
+                return Err(e);
+            }
+        }
+        Ok(TouchPanel { panel, info })
+    }
+
+    pub fn panel(&self) -> Panel {
+        self.panel
+    }
+
+    /// Sets whether force of the touches is measured, without it reports
+    /// have a force of zero.
+    #[doc(alias = "sceTouchEnableTouchForce")]
+    #[doc(alias = "sceTouchDisableTouchForce")]
+    pub fn set_force_enabled(&self, enable: bool) -> SceResult<()> {
+        sce_result_unit_from_code(unsafe {
+            match enable {
+                true => sceTouchEnableTouchForce(self.panel.to_raw()),
+                false => sceTouchDisableTouchForce(self.panel.to_raw()),
+            }
+        })
+    }
+
+    /// Returns the latest sample without waiting.
+    #[doc(alias = "sceTouchPeek")]
+    pub fn peek(&self) -> SceResult<TouchFrame> {
+        self.sample(sceTouchPeek)
+    }
+
+    /// Waits for the next sample.
+    #[doc(alias = "sceTouchRead")]
+    pub fn read(&self) -> SceResult<TouchFrame> {
+        self.sample(sceTouchRead)
+    }
+
+    /// Does the same thing as drop, but you could handle the error case.
+    pub fn close(self) -> SceResult<()> {
+        core::mem::ManuallyDrop::new(self).close_()
+    }
+
+    fn close_(&mut self) -> SceResult<()> {
+        match self.panel.users().fetch_sub(1, Ordering::AcqRel) {
+            1 => set_sampling(self.panel, false),
+            _ => Ok(()),
+        }
+    }
+
+    fn sample(
+        &self,
+        f: unsafe extern "C" fn(u32, *mut SceTouchData, u32) -> core::ffi::c_int,
+    ) -> SceResult<TouchFrame> {
+        let mut data = MaybeUninit::<SceTouchData>::zeroed();
+        sce_result_usize_from_code(unsafe { f(self.panel.to_raw(), data.as_mut_ptr(), 1) })?;
+        let data = unsafe { data.assume_init() };
+
+        let mut frame = TouchFrame {
+            timestamp: data.timeStamp,
+            reports: [TouchReport::default(); SCE_TOUCH_MAX_REPORT as usize],
+            len: data.reportNum.min(SCE_TOUCH_MAX_REPORT) as usize,
+        };
+        for (report, raw) in frame.reports.iter_mut().zip(&data.report[..frame.len]) {
+            *report = self.convert(raw);
+        }
+        Ok(frame)
+    }
+
+    fn convert(&self, raw: &SceTouchReport) -> TouchReport {
+        let info = &self.info;
+        let scale = |value: i16, min: i16, max: i16, size: f32| {
+            let range = (max as f32 - min as f32).max(1.0);
+            ((value as f32 - min as f32) * size / range).clamp(0.0, size)
+        };
+        let force_range = (info.maxForce as f32 - info.minForce as f32).max(1.0);
+        TouchReport {
+            id: raw.id,
+            position: Point {
+                x: scale(raw.x, info.minAaX, info.maxAaX, SCREEN_WIDTH),
+                y: scale(raw.y, info.minAaY, info.maxAaY, SCREEN_HEIGHT),
+            },
+            force: ((raw.force as f32 - info.minForce as f32) / force_range).clamp(0.0, 1.0),
+            raw_x: raw.x,
+            raw_y: raw.y,
+        }
+    }
+}
+
+impl Drop for TouchPanel {
+    fn drop(&mut self) {
+        let _ = self.close_();
+    }
+}
+
+fn set_sampling(panel: Panel, start: bool) -> SceResult<()> {
+    let state = match start {
+        true => SCE_TOUCH_SAMPLING_STATE_START,
+        false => SCE_TOUCH_SAMPLING_STATE_STOP,
+    };
+    sce_result_unit_from_code(unsafe { sceTouchSetSamplingState(panel.to_raw(), state) })
+}